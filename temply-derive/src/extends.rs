@@ -0,0 +1,138 @@
+use crate::parser::ast;
+use crate::ws::inner_asts_mut;
+use crate::{canonicalize, Context};
+use std::collections::HashMap;
+
+/// Resolves the chain of `extends`. Each parent template is loaded with `ctx` and its blocks are
+/// replaced by the blocks of its children. Finally, `f` is called with the resolved base template.
+pub fn resolve<R>(
+    ast: ast::Ast<'_>,
+    ctx: &Context,
+    f: impl FnOnce(ast::Ast<'_>) -> syn::Result<R>,
+) -> syn::Result<R> {
+    resolve_blocks(ast, HashMap::new(), ctx, f)
+}
+
+fn resolve_blocks<'s, R, F>(
+    mut ast: ast::Ast<'s>,
+    mut blocks: HashMap<&'s str, ast::Ast<'s>>,
+    ctx: &Context,
+    f: F,
+) -> syn::Result<R>
+where
+    F: FnOnce(ast::Ast<'_>) -> syn::Result<R>,
+{
+    let parent = ast.items.iter().find_map(|item| match item {
+        ast::Item::Extends(parent) => Some(*parent),
+        _ => None,
+    });
+
+    match parent {
+        Some(parent) => {
+            // Merge own blocks with the blocks of the children
+            let mut own_blocks = Vec::new();
+            collect_blocks(&mut ast.items, &mut own_blocks);
+            for (name, body) in own_blocks {
+                let body = match blocks.remove(name) {
+                    Some(mut child) => {
+                        replace_super(&mut child.items, name, &body);
+                        child
+                    }
+                    None => body,
+                };
+                blocks.insert(name, body);
+            }
+
+            // Load parent, which stays on the include stack while the base template is generated
+            let full_path = canonicalize(&ctx.root_path.join(parent));
+            ctx.check_cycle(parent, &full_path)?;
+            ctx.includes.borrow_mut().push(full_path);
+            let source = ctx.load(parent)?;
+            let parent = ctx.parse(&source)?;
+
+            let result = resolve_blocks(parent, blocks, ctx, f);
+            ctx.includes.borrow_mut().pop();
+            result
+        }
+        None => {
+            replace_blocks(&mut ast.items, &mut blocks);
+            if let Some(name) = blocks.keys().min() {
+                return Err(ctx.error(
+                    name,
+                    &format!("block `{}` is not defined in any parent template", name),
+                ));
+            }
+            check_resolved(&mut ast.items, ctx)?;
+
+            f(ast)
+        }
+    }
+}
+
+fn collect_blocks<'s>(items: &mut [ast::Item<'s>], blocks: &mut Vec<(&'s str, ast::Ast<'s>)>) {
+    for item in items {
+        if let ast::Item::Block { name, body } = item {
+            blocks.push((*name, body.clone()));
+        }
+        for ast in inner_asts_mut(item) {
+            collect_blocks(&mut ast.items, blocks);
+        }
+    }
+}
+
+fn replace_blocks<'s>(items: &mut [ast::Item<'s>], blocks: &mut HashMap<&'s str, ast::Ast<'s>>) {
+    for item in items {
+        if let ast::Item::Block { name, body } = item {
+            if let Some(mut child) = blocks.remove(name) {
+                replace_super(&mut child.items, name, body);
+                *body = child;
+            }
+        }
+        for ast in inner_asts_mut(item) {
+            replace_blocks(&mut ast.items, blocks);
+        }
+    }
+}
+
+fn replace_super<'s>(items: &mut [ast::Item<'s>], name: &'s str, parent: &ast::Ast<'s>) {
+    for item in items {
+        match item {
+            ast::Item::Super(_) => {
+                *item = ast::Item::Block {
+                    name,
+                    body: parent.clone(),
+                }
+            }
+            // Super inside of another block refers to the other block
+            ast::Item::Block { name: other, .. } if *other != name => (),
+            _ => {
+                for ast in inner_asts_mut(item) {
+                    replace_super(&mut ast.items, name, parent);
+                }
+            }
+        }
+    }
+}
+
+fn check_resolved(items: &mut [ast::Item<'_>], ctx: &Context) -> syn::Result<()> {
+    for item in items {
+        match item {
+            ast::Item::Extends(parent) => {
+                return Err(ctx.error(parent, "extends must be at the top level of a template"))
+            }
+            ast::Item::Super(super_) => {
+                return Err(ctx.error(
+                    super_,
+                    "super must be inside of a block overriding a parent block",
+                ))
+            }
+            _ => {
+                for ast in inner_asts_mut(item) {
+                    check_resolved(&mut ast.items, ctx)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    FnArg, GenericParam, Ident, ItemFn, Lifetime, LifetimeDef, ParenthesizedGenericArguments, Pat,
+    ReturnType, Type, TypeBareFn, TypeReference,
+};
+
+/// Expands `#[template(...)]` on a function into a struct with one field per parameter, which
+/// implements `Template`, and a function constructing it.
+pub fn expand(args: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let mut function = syn::parse2::<ItemFn>(item)?;
+    let attrs = template_attrs(args)?;
+
+    // Check signature
+    let sig = &mut function.sig;
+    match &sig.output {
+        ReturnType::Type(_, ty) if matches!(**ty, Type::ImplTrait(_)) => (),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &sig.output,
+                "template functions must return `impl Template`",
+            ))
+        }
+    }
+    if !function.block.stmts.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &function.block,
+            "the body of a template function must be empty",
+        ));
+    }
+
+    // Name elided lifetimes, so that they can be used in the struct
+    let mut elided = ElidedLifetimes::default();
+    for input in &mut sig.inputs {
+        if let FnArg::Typed(input) = input {
+            elided.visit_type_mut(&mut input.ty);
+        }
+    }
+    for (idx, lifetime) in elided.lifetimes.into_iter().enumerate() {
+        sig.generics
+            .params
+            .insert(idx, GenericParam::Lifetime(LifetimeDef::new(lifetime)));
+    }
+
+    // Collect fields
+    let fields = sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            FnArg::Typed(input) => match &*input.pat {
+                Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => {
+                    Ok((&pat.ident, &input.ty))
+                }
+                pat => Err(syn::Error::new_spanned(
+                    pat,
+                    "parameters of template functions must be identifiers",
+                )),
+            },
+            FnArg::Receiver(receiver) => Err(syn::Error::new_spanned(
+                receiver,
+                "template functions can not take `self`",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let names = fields.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let types = fields.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+
+    // Generate
+    let vis = &function.vis;
+    let fn_attrs = &function.attrs;
+    let fn_name = &sig.ident;
+    let name = Ident::new(&pascal_case(&fn_name.to_string()), fn_name.span());
+    let (impl_generics, ty_generics, where_clause) = sig.generics.split_for_impl();
+    let params = &sig.generics.params;
+    let generics = if params.is_empty() {
+        None
+    } else {
+        Some(quote! { <#params> })
+    };
+    let doc = format!("The template of [`{}`].", fn_name);
+
+    let fields = quote! {
+        #(#vis #names: #types,)*
+    };
+    let template = crate::derive(quote! {
+        #(#attrs)*
+        struct #name #generics #where_clause { #fields }
+    })?;
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #name #generics #where_clause { #fields }
+
+        #template
+
+        #(#fn_attrs)*
+        #vis fn #fn_name #impl_generics (#(#names: #types),*) -> #name #ty_generics #where_clause {
+            #name { #(#names),* }
+        }
+    })
+}
+
+/// Converts the options of `#[template(...)]` into the attributes of the derive. `path` and
+/// `inline` become the template source, `dedent` becomes `#[dedent]` and everything else is
+/// passed on in `#[template(...)]`.
+fn template_attrs(args: TokenStream) -> syn::Result<Vec<TokenStream>> {
+    let mut options = vec![Vec::new()];
+    for token in args {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => options.push(Vec::new()),
+            token => options.last_mut().unwrap().push(token),
+        }
+    }
+
+    let mut attrs = Vec::new();
+    let mut rest = Vec::new();
+    let mut source = false;
+    for option in options.into_iter().filter(|option| !option.is_empty()) {
+        let name = match option.first() {
+            Some(TokenTree::Ident(name)) => name.to_string(),
+            _ => String::new(),
+        };
+        let value = option.iter().skip(2);
+        match name.as_str() {
+            "path" | "inline" if option.len() > 2 => {
+                let attr = if name == "path" {
+                    format_ident!("template")
+                } else {
+                    format_ident!("template_inline")
+                };
+                attrs.push(quote! { #[#attr = #(#value)*] });
+                source = true;
+            }
+            "dedent" if option.len() == 1 => attrs.push(quote! { #[dedent] }),
+            "name" | "default" => {
+                return Err(syn::Error::new_spanned(
+                    &option[0],
+                    "named templates are not supported on functions",
+                ))
+            }
+            _ => rest.push(option.into_iter().collect::<TokenStream>()),
+        }
+    }
+    if !source {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "missing template source, add `path = \"...\"` or `inline = \"...\"`",
+        ));
+    }
+    if !rest.is_empty() {
+        attrs.push(quote! { #[template(#(#rest),*)] });
+    }
+
+    Ok(attrs)
+}
+
+/// Replaces elided lifetimes of references and `'_` with new named lifetimes.
+#[derive(Default)]
+struct ElidedLifetimes {
+    lifetimes: Vec<Lifetime>,
+}
+
+impl ElidedLifetimes {
+    fn next(&mut self, span: Span) -> Lifetime {
+        let lifetime = Lifetime::new(&format!("'__t{}", self.lifetimes.len()), span);
+        self.lifetimes.push(lifetime.clone());
+        lifetime
+    }
+}
+
+impl VisitMut for ElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.next(reference.and_token.span));
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.next(lifetime.apostrophe);
+        }
+    }
+
+    // Lifetimes in function pointers and `Fn` traits are elided on their own
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {
+    }
+}
+
+/// Converts a snake case name to pascal case.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
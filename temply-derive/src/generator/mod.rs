@@ -1,51 +1,137 @@
-use std::path::Path;
+use std::path::PathBuf;
 
 use crate::parser::ast;
-use proc_macro2::{Span, TokenStream};
-use quote::quote;
-use syn::{Data, Generics, Ident};
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{Data, Fields, Generics, Ident, Variant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    None,
+    Html,
+}
+
+pub struct Context<'a> {
+    pub escape: Escape,
+    pub filters: &'a syn::Path,
+    pub include: &'a dyn Fn(&str) -> syn::Result<TokenStream>,
+    pub import: &'a dyn Fn(&str, Option<&str>) -> syn::Result<TokenStream>,
+    /// The namespace of an imported template, which prefixes its macros
+    pub namespace: Option<&'a str>,
+    pub error: &'a dyn Fn(&str, &str) -> syn::Error,
+}
 
 pub fn generate(
     name: &Ident,
     data: &Data,
     generics: &Generics,
-    path: Option<&Path>,
-    ast: ast::Ast<'_>,
+    paths: &[PathBuf],
+    body: TokenStream,
+    size_hint: usize,
+    methods: Vec<(String, TokenStream, usize)>,
 ) -> TokenStream {
     // Recompile if template changes
-    let recompile_on_change = match path {
-        Some(path) => {
-            let path = path.to_str().unwrap();
-            Some(quote! {
-                const _: &str = include_str!(#path); // Recompile if template changes
-            })
+    let recompile_on_change = paths.iter().map(|path| {
+        let path = path.to_str().unwrap();
+        quote! {
+            const _: &str = include_str!(#path); // Recompile if template changes
         }
-        None => None,
-    };
+    });
 
     // Generics
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     // Destruct self
-    let destruct_self = generate_destruct_self(&name, data);
+    let destruct_self = generate_destruct_self(name, data);
 
-    // Ast
-    let ast = generate_ast(ast);
+    // Render methods of named templates
+    let methods = if methods.is_empty() {
+        None
+    } else {
+        let methods = methods.into_iter().map(|(name, body, size_hint)| {
+            let render = format_ident!("render_{}", name);
+            let render_to_string = format_ident!("render_{}_to_string", name);
+            let write_to = format_ident!("write_{}_to", name);
+            let write_to_buffered = format_ident!("write_{}_to_buffered", name);
+            quote! {
+                pub fn #render(&self, mut __buffer: impl ::std::fmt::Write) -> ::std::fmt::Result {
+                    let __buffer = &mut __buffer;
+                    #destruct_self
+                    #body
+                    Ok(())
+                }
+
+                pub fn #render_to_string(&self) -> ::std::string::String {
+                    ::temply::__intern::io::render_to_string(#size_hint, |buffer| {
+                        self.#render(buffer)
+                    })
+                }
+
+                pub fn #write_to(&self, writer: impl ::std::io::Write) -> ::std::io::Result<()> {
+                    ::temply::__intern::io::write_to(writer, |writer| self.#render(writer))
+                }
+
+                pub fn #write_to_buffered(
+                    &self,
+                    writer: impl ::std::io::Write,
+                ) -> ::std::io::Result<()> {
+                    self.#write_to(::std::io::BufWriter::new(writer))
+                }
+            }
+        });
+        Some(quote! {
+            #[allow(dead_code)]
+            impl #impl_generics #name #ty_generics #where_clause {
+                #(#methods)*
+            }
+        })
+    };
 
     quote! {
-        #recompile_on_change
+        #(#recompile_on_change)*
+
+        #methods
 
         impl #impl_generics ::temply::Template for #name #ty_generics #where_clause {
+            const SIZE_HINT: usize = #size_hint;
+
             fn render(&self, mut __buffer: impl ::std::fmt::Write) -> ::std::fmt::Result {
                 let __buffer = &mut __buffer;
                 #destruct_self
-                #ast
+                #body
                 Ok(())
             }
         }
     }
 }
 
+/// Generates a `Display` implementation, which renders the template. If a width or precision is
+/// given, the rendered template is padded or truncated as a whole.
+pub fn generate_display(name: &Ident, generics: &Generics, escape: Escape) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let pad = match escape {
+        Escape::None => quote! { f.pad(&rendered) },
+        Escape::Html => quote! { ::temply::__intern::escape::raw(|| f.pad(&rendered)) },
+    };
+
+    quote! {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                if f.width().is_none() && f.precision().is_none() {
+                    ::temply::Template::render(self, f)
+                } else {
+                    let mut rendered = ::std::string::String::with_capacity(
+                        <Self as ::temply::Template>::SIZE_HINT,
+                    );
+                    ::temply::Template::render(self, &mut rendered)?;
+                    #pad
+                }
+            }
+        }
+    }
+}
+
 fn generate_destruct_self(name: &Ident, data: &Data) -> Option<TokenStream> {
     let data = match data {
         Data::Struct(data) => data,
@@ -66,16 +152,69 @@ fn generate_destruct_self(name: &Ident, data: &Data) -> Option<TokenStream> {
     })
 }
 
-fn generate_ast(ast: ast::Ast<'_>) -> TokenStream {
-    let items = ast.items.into_iter().map(generate_item);
+/// Generates a match on `self`, which renders the body of the matching variant with its fields
+/// bound by name or as `_0`, `_1`, ... Variants without a body render `fallback`.
+pub fn generate_variants(
+    variants: Vec<(&Variant, Option<TokenStream>)>,
+    fallback: Option<TokenStream>,
+) -> TokenStream {
+    let mut has_fallback = false;
+    let arms = variants
+        .into_iter()
+        .filter_map(|(variant, body)| {
+            let body = match body {
+                Some(body) => body,
+                None => {
+                    has_fallback = true;
+                    return None;
+                }
+            };
+            let ident = &variant.ident;
+            let pattern = match &variant.fields {
+                Fields::Named(fields) => {
+                    let names = fields.named.iter().map(|field| &field.ident);
+                    quote! { Self::#ident { #(#names,)* } }
+                }
+                Fields::Unnamed(fields) => {
+                    let names = (0..fields.unnamed.len()).map(|idx| format_ident!("_{}", idx));
+                    quote! { Self::#ident( #(#names,)* ) }
+                }
+                Fields::Unit => quote! { Self::#ident },
+            };
+            Some(quote! {
+                #[allow(unused)]
+                #pattern => { #body }
+            })
+        })
+        .collect::<Vec<_>>();
+    let fallback = if has_fallback {
+        Some(quote! { _ => { #fallback } })
+    } else {
+        None
+    };
 
     quote! {
-        #(#items)*
+        match self {
+            #(#arms)*
+            #fallback
+        }
     }
 }
 
-fn generate_item(item: ast::Item<'_>) -> TokenStream {
-    match item {
+pub fn generate_ast(ast: ast::Ast<'_>, ctx: &Context<'_>) -> syn::Result<TokenStream> {
+    let items = ast
+        .items
+        .into_iter()
+        .map(|item| generate_item(item, ctx))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #(#items)*
+    })
+}
+
+fn generate_item(item: ast::Item<'_>, ctx: &Context<'_>) -> syn::Result<TokenStream> {
+    Ok(match item {
         ast::Item::Text(text) => {
             let text = text_to_string(text);
             quote! {
@@ -83,34 +222,153 @@ fn generate_item(item: ast::Item<'_>) -> TokenStream {
             }
         }
         ast::Item::Comment(_) => quote! {},
-        ast::Item::Expr(expr, format) => {
-            let expr = expr.parse::<TokenStream>().unwrap();
+        ast::Item::Expr {
+            expr,
+            filters,
+            format,
+            ind,
+        } => {
+            let expr = parse_tokens(expr, ctx)?;
+            let expr = filters.into_iter().try_fold(expr, |expr, filter| {
+                let filters = ctx.filters;
+                let name = parse_ident(filter.name, ctx)?;
+                let args = filter
+                    .args
+                    .iter()
+                    .map(|arg| parse_tokens(arg, ctx))
+                    .collect::<syn::Result<Vec<_>>>()?;
+                Ok::<_, syn::Error>(quote! { #filters::#name(#expr, #(#args),*) })
+            })?;
+            // The site lets `NoIndent` disable the indentation of this expression only
+            let write = if ind == 0 {
+                quote! { __buffer }
+            } else {
+                let indentation = " ".repeat(ind);
+                quote! {
+                    &mut ::temply::__intern::indent::Indenter::expr(__buffer, #indentation, &__site)
+                }
+            };
+            let write = match ctx.escape {
+                Escape::None => quote! {
+                    ::std::fmt::Write::write_fmt(#write, ::std::format_args!(#format, #expr))?;
+                },
+                Escape::Html => quote! {
+                    ::std::fmt::Write::write_fmt(
+                        &mut ::temply::__intern::escape::Html::new(#write),
+                        ::std::format_args!(#format, #expr),
+                    )?;
+                },
+            };
             quote! {
-                ::std::write!(__buffer, #format, #expr)?;
+                {
+                    let __site = ::temply::__intern::indent::Site::enter();
+                    #write
+                }
             }
         }
         ast::Item::Let(let_) => {
-            let let_ = let_.parse::<TokenStream>().unwrap();
+            let let_ = parse_tokens(let_, ctx)?;
             quote! { #let_; }
         }
+        ast::Item::Use(use_) => {
+            // Items are visible in the whole enclosing block, so the use can stay in place
+            let use_ = parse_tokens(use_.trim_end_matches(';'), ctx)?;
+            quote! { #use_; }
+        }
         ast::Item::Scope(body) => {
-            let body = generate_ast(body);
+            let body = generate_ast(body, ctx)?;
             quote! { { #body } }
         }
+        ast::Item::Indent { indent, body } => {
+            let indent = parse_tokens(indent, ctx)?;
+            let body = generate_ast(body, ctx)?;
+            quote! {
+                {
+                    let __prefix = &(#indent);
+                    let __prefix = ::temply::__intern::indent::Prefix::prefix(__prefix);
+                    let __buffer: &mut dyn ::std::fmt::Write =
+                        &mut ::temply::__intern::indent::Indenter::prefixed(
+                        __buffer,
+                        &__prefix,
+                    );
+                    #body
+                }
+            }
+        }
+        ast::Item::Capture { name, body } => {
+            // The variable is visible after the block, so only the body gets its own scope
+            let name = parse_tokens(name, ctx)?;
+            let capacity = size_hint(&body, &|_| 0);
+            let body = generate_ast(body, ctx)?;
+            quote! {
+                let #name = {
+                    let mut __capture = ::std::string::String::with_capacity(#capacity);
+                    {
+                        let __buffer: &mut dyn ::std::fmt::Write = &mut __capture;
+                        #body
+                    }
+                    __capture
+                };
+            }
+        }
         ast::Item::For {
+            label,
             for_,
             pre,
             body,
             else_,
         } => {
-            let for_ = for_.parse::<TokenStream>().unwrap();
+            let label = generate_label(label, ctx)?;
+            let for_ = parse_tokens(for_, ctx)?;
             let pre = pre.map(text_to_string).unwrap_or_default();
-            let body = generate_ast(body);
-            let else_ = else_.map(|body| generate_ast(body));
+            let (body, usage) = rewrite_loop(generate_ast(body, ctx)?);
+            let else_ = else_.map(|body| generate_ast(body, ctx)).transpose()?;
+            if usage == LoopUsage::None {
+                return Ok(quote! {
+                    {
+                        let mut __first = true;
+                        #label #for_ {
+                            if !__first {
+                                ::std::write!(__buffer, "{}", #pre)?;
+                            }
+                            __first = false;
+                            #body
+                        }
+                        if __first {
+                            #else_
+                        }
+                    }
+                });
+            }
+
+            // Drive the iterator by hand to provide the loop variable
+            let (pat, iter) = split_for(for_);
+            let (length, sized_loop) = if usage == LoopUsage::Sized {
+                (
+                    Some(quote! { let __length = ::std::iter::ExactSizeIterator::len(&__iter); }),
+                    Some(quote! {
+                        let __loop = ::temply::__intern::loop_::SizedLoop::new(__loop, __length);
+                    }),
+                )
+            } else {
+                (None, None)
+            };
             quote! {
                 {
                     let mut __first = true;
-                    #for_ {
+                    let mut __iter = ::std::iter::Iterator::peekable(
+                        ::std::iter::IntoIterator::into_iter(#iter),
+                    );
+                    #length
+                    let mut __index0 = 0;
+                    #label while let ::std::option::Option::Some(__item) = ::std::iter::Iterator::next(&mut __iter) {
+                        let __loop = ::temply::__intern::loop_::Loop::new(
+                            __index0,
+                            __iter.peek().is_none(),
+                        );
+                        #sized_loop
+                        __index0 += 1;
+                        let #pat = __item;
                         if !__first {
                             ::std::write!(__buffer, "{}", #pre)?;
                         }
@@ -123,21 +381,46 @@ fn generate_item(item: ast::Item<'_>) -> TokenStream {
                 }
             }
         }
+        ast::Item::While {
+            label,
+            while_,
+            body,
+        } => {
+            let label = generate_label(label, ctx)?;
+            let while_ = parse_tokens(while_, ctx)?;
+            let body = generate_ast(body, ctx)?;
+            quote! {
+                #label #while_ {
+                    #body
+                }
+            }
+        }
+        ast::Item::Break(break_) => {
+            let break_ = parse_tokens(break_, ctx)?;
+            quote! { #break_; }
+        }
+        ast::Item::Continue(continue_) => {
+            let continue_ = parse_tokens(continue_, ctx)?;
+            quote! { #continue_; }
+        }
         ast::Item::If {
             if_,
             else_ifs,
             else_,
         } => {
-            let if_body = generate_ast(if_.1);
-            let if_ = if_.0.parse::<TokenStream>().unwrap();
-            let else_ifs = else_ifs.into_iter().map(|(else_if, body)| {
-                let else_if = else_if.parse::<TokenStream>().unwrap();
-                let body = generate_ast(body);
-                quote! { #else_if { #body } }
-            });
+            let if_body = generate_ast(if_.1, ctx)?;
+            let if_ = parse_tokens(if_.0, ctx)?;
+            let else_ifs = else_ifs
+                .into_iter()
+                .map(|(else_if, body)| {
+                    let else_if = parse_tokens(else_if, ctx)?;
+                    let body = generate_ast(body, ctx)?;
+                    Ok(quote! { #else_if { #body } })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
             let else_ = match else_ {
                 Some(body) => {
-                    let body = generate_ast(body);
+                    let body = generate_ast(body, ctx)?;
                     Some(quote! { else { #body } })
                 }
                 None => None,
@@ -151,14 +434,17 @@ fn generate_item(item: ast::Item<'_>) -> TokenStream {
             }
         }
         ast::Item::Match { match_, wheres } => {
-            let match_ = match_.parse::<TokenStream>().unwrap();
-            let cases = wheres.into_iter().map(|(arm, body)| {
-                let arm = arm.parse::<TokenStream>().unwrap();
-                let body = generate_ast(body);
-                quote! {
-                    #arm => { #body }
-                }
-            });
+            let match_ = parse_tokens(match_, ctx)?;
+            let cases = wheres
+                .into_iter()
+                .map(|(arm, body)| {
+                    let arm = parse_tokens(arm, ctx)?;
+                    let body = generate_ast(body, ctx)?;
+                    Ok(quote! {
+                        #arm => { #body }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
             quote! {
                 #match_ {
                     #(#cases)*
@@ -166,51 +452,184 @@ fn generate_item(item: ast::Item<'_>) -> TokenStream {
             }
         }
         ast::Item::Macro { name, params, body } => {
-            let struct_name = Ident::new(&format!("__closure_{}", name), Span::call_site());
-            let struct_name_var = Ident::new(&format!("__closure_{}_var", name), Span::call_site());
+            let name = macro_name(name, ctx)?;
+            let struct_name = format_ident!("__closure_{}", name);
+            let struct_name_var = format_ident!("__closure_{}_var", name);
 
             let params = params
                 .iter()
-                .map(|param| param.parse::<TokenStream>().unwrap())
-                .collect::<Vec<_>>();
+                .map(|param| parse_tokens(param, ctx))
+                .collect::<syn::Result<Vec<_>>>()?;
             let generics = (0..params.len())
                 .map(|idx| Ident::new(&format!("T{}", idx), Span::call_site()))
                 .collect::<Vec<_>>();
 
-            let body = generate_ast(body);
+            let body = generate_ast(body, ctx)?;
 
             quote! {
                 struct #struct_name<'c, #(#generics),*> {
-                    f: &'c dyn Fn(&#struct_name<'c, #(#generics),*>, &mut dyn ::std::fmt::Write, #(#generics),*) -> ::std::fmt::Result
+                    f: &'c dyn Fn(&#struct_name<'c, #(#generics),*>, &mut dyn ::std::fmt::Write, &dyn Fn(&mut dyn ::std::fmt::Write) -> ::std::fmt::Result, #(#generics),*) -> ::std::fmt::Result
                 }
                 let #struct_name_var = #struct_name {
-                    f: &|#struct_name_var, __buffer, #(#params),*| {
+                    f: &|#struct_name_var, __buffer, __caller, #(#params),*| {
                         #body
                         Ok(())
                     }
                 };
             }
         }
-        ast::Item::Call { name, args, ind } => {
-            let struct_name_var = Ident::new(&format!("__closure_{}_var", name), Span::call_site());
+        ast::Item::Call {
+            name,
+            args,
+            caller,
+            ind,
+        } => {
+            let name = macro_name(name, ctx)?;
+            let struct_name_var = format_ident!("__closure_{}_var", name);
             let args = args
                 .iter()
-                .map(|arg| arg.parse::<TokenStream>().unwrap())
-                .collect::<Vec<_>>();
-            let write = if ind == 0 {
-                quote! { __buffer }
-            } else {
-                let indentation = (0..ind).map(|_| ' ').collect::<String>();
-                quote! { &mut ::temply::__intern::indent::Indenter::new(__buffer, #indentation) }
+                .map(|arg| parse_tokens(arg, ctx))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let caller = match caller {
+                Some(caller) => generate_ast(caller, ctx)?,
+                None => quote! {},
             };
+            let write = generate_indenter(ind);
 
             quote! {
-                (#struct_name_var.f)(&#struct_name_var, #write, #(#args),*)?;
+                (#struct_name_var.f)(&#struct_name_var, #write, &|__buffer: &mut dyn ::std::fmt::Write| {
+                    #caller
+                    Ok(())
+                }, #(#args),*)?;
+            }
+        }
+        ast::Item::Caller { caller: _, ind } => {
+            let write = generate_indenter(ind);
+            quote! {
+                __caller(#write)?;
             }
         }
+        ast::Item::Block { name: _, body } => {
+            let body = generate_ast(body, ctx)?;
+            quote! { { #body } }
+        }
+        ast::Item::Include(path) => (ctx.include)(path)?,
+        ast::Item::Import { path, namespace } => (ctx.import)(path, namespace)?,
+        ast::Item::Extends(_) | ast::Item::Super(_) => {
+            unreachable!("extends and super are resolved before generating")
+        }
+    })
+}
+
+/// Generates the buffer of a call, which indents all lines after the first by `ind` spaces.
+fn generate_indenter(ind: usize) -> TokenStream {
+    if ind == 0 {
+        quote! { __buffer }
+    } else {
+        let indentation = (0..ind).map(|_| ' ').collect::<String>();
+        quote! { &mut ::temply::__intern::indent::Indenter::new(__buffer, #indentation) }
     }
 }
 
+/// Generates the label of a loop, including the colon.
+fn generate_label(label: Option<&str>, ctx: &Context<'_>) -> syn::Result<Option<TokenStream>> {
+    label
+        .map(|label| {
+            let label = parse_tokens(label, ctx)?;
+            Ok(quote! { #label: })
+        })
+        .transpose()
+}
+
+/// Parses a piece of Rust code of the template.
+fn parse_tokens(code: &str, ctx: &Context<'_>) -> syn::Result<TokenStream> {
+    code.parse()
+        .map_err(|error| (ctx.error)(code, &format!("invalid rust code: {}", error)))
+}
+
+/// Returns the name of the variable of a macro, which is `<NAMESPACE>__<NAME>` for macros in a
+/// namespace. Names without a namespace are in the namespace of the current template.
+fn macro_name(name: &str, ctx: &Context<'_>) -> syn::Result<String> {
+    let (namespace, name) = match name.split_once("::") {
+        Some((namespace, name)) => (Some(namespace.trim()), name.trim()),
+        None => (ctx.namespace, name),
+    };
+    let name = parse_ident(name, ctx)?;
+    Ok(match namespace {
+        Some(namespace) => format!("{}__{}", parse_ident(namespace, ctx)?, name),
+        None => name.to_string(),
+    })
+}
+
+/// Parses the name of a macro or filter.
+fn parse_ident(name: &str, ctx: &Context<'_>) -> syn::Result<Ident> {
+    syn::parse_str(name).map_err(|_| (ctx.error)(name, &format!("invalid name `{}`", name)))
+}
+
+/// Assumed number of iterations of a for loop when computing the size hint.
+const LOOP_WEIGHT: usize = 4;
+
+/// Computes the size hint of a template from the length of its static text. Branches count with
+/// their largest size and bodies of for loops are weighted with [`LOOP_WEIGHT`].
+pub fn size_hint(ast: &ast::Ast<'_>, include: &dyn Fn(&str) -> usize) -> usize {
+    ast.items
+        .iter()
+        .map(|item| match item {
+            ast::Item::Text(text) => text_len(text),
+            ast::Item::Scope(body)
+            | ast::Item::Indent { body, .. }
+            | ast::Item::Block { body, .. } => size_hint(body, include),
+            ast::Item::For {
+                pre, body, else_, ..
+            } => {
+                let pre = pre.as_ref().map_or(0, text_len);
+                let body = (pre + size_hint(body, include)) * LOOP_WEIGHT;
+                let else_ = else_.as_ref().map_or(0, |else_| size_hint(else_, include));
+                body.max(else_)
+            }
+            ast::Item::While { body, .. } => size_hint(body, include) * LOOP_WEIGHT,
+            ast::Item::If {
+                if_,
+                else_ifs,
+                else_,
+            } => Some(&if_.1)
+                .into_iter()
+                .chain(else_ifs.iter().map(|(_, body)| body))
+                .chain(else_)
+                .map(|body| size_hint(body, include))
+                .max()
+                .unwrap_or(0),
+            ast::Item::Match { wheres, .. } => wheres
+                .iter()
+                .map(|(_, body)| size_hint(body, include))
+                .max()
+                .unwrap_or(0),
+            ast::Item::Include(path) => include(path),
+            ast::Item::Comment(_)
+            | ast::Item::Expr { .. }
+            | ast::Item::Let(_)
+            | ast::Item::Use(_)
+            | ast::Item::Macro { .. }
+            | ast::Item::Capture { .. }
+            | ast::Item::Call { .. }
+            | ast::Item::Caller { .. }
+            | ast::Item::Import { .. }
+            | ast::Item::Extends(_)
+            | ast::Item::Break(_)
+            | ast::Item::Continue(_)
+            | ast::Item::Super(_) => 0,
+        })
+        .sum()
+}
+
+fn text_len(text: &ast::Text<'_>) -> usize {
+    text.lines
+        .iter()
+        .map(|line| line.content.len() + line.new_line.len())
+        .sum::<usize>()
+        + text.trailing.len()
+}
+
 fn text_to_string(text: ast::Text<'_>) -> String {
     let mut buffer = String::new();
     for line in text.lines {
@@ -220,3 +639,63 @@ fn text_to_string(text: ast::Text<'_>) -> String {
     buffer += text.trailing;
     buffer
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LoopUsage {
+    None,
+    Plain,
+    Sized,
+}
+
+/// Replaces the `loop` variable with `__loop` and reports how it is used.
+///
+/// `loop` followed by a brace group is a Rust loop and kept as is.
+fn rewrite_loop(stream: TokenStream) -> (TokenStream, LoopUsage) {
+    let mut usage = LoopUsage::None;
+    let tokens = stream.into_iter().collect::<Vec<_>>();
+    let rewritten = tokens
+        .iter()
+        .enumerate()
+        .map(|(idx, token)| match token {
+            TokenTree::Ident(ident) if ident == "loop" => {
+                match tokens.get(idx + 1) {
+                    Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                        return token.clone();
+                    }
+                    _ => {}
+                }
+                let sized = matches!(
+                    (tokens.get(idx + 1), tokens.get(idx + 2)),
+                    (Some(TokenTree::Punct(dot)), Some(TokenTree::Ident(field)))
+                        if dot.as_char() == '.'
+                            && (field == "length" || field == "revindex" || field == "revindex0")
+                );
+                usage = usage.max(if sized {
+                    LoopUsage::Sized
+                } else {
+                    LoopUsage::Plain
+                });
+                TokenTree::Ident(Ident::new("__loop", ident.span()))
+            }
+            TokenTree::Group(group) => {
+                let (stream, inner) = rewrite_loop(group.stream());
+                usage = usage.max(inner);
+                let mut rewritten = Group::new(group.delimiter(), stream);
+                rewritten.set_span(group.span());
+                TokenTree::Group(rewritten)
+            }
+            _ => token.clone(),
+        })
+        .collect();
+    (rewritten, usage)
+}
+
+/// Splits `for <PAT> in <EXPR>` into pattern and expression.
+fn split_for(for_: TokenStream) -> (TokenStream, TokenStream) {
+    let mut tokens = for_.into_iter().skip(1);
+    let pat = tokens
+        .by_ref()
+        .take_while(|token| !matches!(token, TokenTree::Ident(ident) if ident == "in"))
+        .collect();
+    (pat, tokens.collect())
+}
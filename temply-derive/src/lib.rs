@@ -1,22 +1,62 @@
 #![deny(rust_2018_idioms)]
 
+mod extends;
+mod function;
 mod generator;
-mod lexer;
-mod parser;
-mod ws;
+mod render;
 
-use proc_macro2::TokenStream;
+use generator::Escape;
+use lexer::Syntax;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::quote;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
+use std::io;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use syn::{Data, DeriveInput, Generics, Ident, Lit, Meta};
+use std::rc::Rc;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Data, DeriveInput, Generics, Ident, Lit, LitStr, Meta, Token};
+use temply_parser::{lexer, parser, ws};
 
 /// Derive the `Template` trait.
 ///
-/// The template can be specified with either `#[template]` or `#[template_inline]`.
+/// The template can be specified with either `#[template]` or `#[template_inline]`. Paths, also
+/// the ones of extended, included and imported templates, are relative to the src folder. The
+/// derive recompiles whenever one of the loaded template files changes.
 ///
 /// Use the dedent option to automatically dedent content in blocks.
 ///
+/// Variants of an enum can have their own template, in which the named fields of the variant are
+/// available by name and positional fields as `_0`, `_1`, ... Variants without a template render
+/// the template of the enum. Options of the enum apply to all variants, unless a variant with its
+/// own template overrides them.
+///
+/// Additional templates can be added with `#[template(name = "header", path = "...")]` or
+/// `#[template(name = "header", inline = "...")]`. Each one generates a `render_<name>` method with
+/// the same signature as `Template::render`, and `render_<name>_to_string`, `write_<name>_to` and
+/// `write_<name>_to_buffered` methods like the provided methods of `Template`. Without a `#[template]` or `#[template_inline]`, one
+/// of them must be marked with `default` to implement `Template`. Options like `escape` can be set
+/// per named template.
+///
+/// Use `#[template(escape = "html")]` to html-escape all expressions. Templates loaded from a file
+/// with an `html` extension are escaped by default, which can be disabled with
+/// `#[template(escape = "none")]`.
+///
+/// Use `#[template(display)]` to also implement `Display` by rendering the template. A width,
+/// fill, alignment and precision given in the format string apply to the whole output.
+///
+/// Use `#[template(syntax(expr = ("<<", ">>"), block = ("<%", "%>"), comment = ("<#", "#>")))]`
+/// to change the delimiters of the template. Delimiters that are left out keep their default.
+///
+/// Use `#[template(filters = path::to::module)]` to resolve filters in a custom module instead of
+/// `temply::filters`. The module usually re-exports the builtin filters with
+/// `pub use temply::filters::*;`.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -32,79 +72,840 @@ use syn::{Data, DeriveInput, Generics, Ident, Lit, Meta};
 /// #[template_inline = "Hello {{ name }}!"]
 /// struct MyTemplate<'a> { name: &'a str }
 /// ```
+///
+/// ```ignore
+/// # use temply::Template;
+/// #[derive(Debug, Template)]
+/// enum MyTemplate<'a> {
+///     #[template_inline = "Hello {{ name }}!"]
+///     Hello { name: &'a str },
+///     #[template_inline = "Bye {{ _0 }}!"]
+///     Bye(&'a str),
+/// }
+/// ```
 #[proc_macro_derive(Template, attributes(template, template_inline, dedent))]
 pub fn derive_template(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    derive(input.into())
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Turn a function into a template.
+///
+/// The attribute generates a struct with one field per parameter, which implements `Template`,
+/// and replaces the function with one that constructs it. The struct is named after the function
+/// in pascal case and the function returns it instead of `impl Template`. The body of the function
+/// must be empty. Elided lifetimes of references and `'_` are turned into lifetime parameters of
+/// the struct.
+///
+/// The template is given by `path = "..."` or `inline = "..."`. All other options of
+/// `#[template(...)]` are supported, except for named templates, and `dedent` dedents the template.
+///
+/// # Examples
+///
+/// ```ignore
+/// # use temply::Template;
+/// #[temply::template(inline = "# {{ title }}\n{% for item in items %}- {{ item }}\n{% endfor %}")]
+/// fn render_list(title: &str, items: &[&str]) -> impl Template {}
+///
+/// let rendered = render_list("List", &["a", "b"]).render_to_string();
+/// ```
+#[proc_macro_attribute]
+pub fn template(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    function::expand(args.into(), item.into())
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Render an inline template to a string, like `format!`.
+///
+/// Variables of the enclosing scope can be used in the template, but not moved out of, since the
+/// template may be rendered more than once. Additional values can be passed as named arguments,
+/// which are all evaluated before any of them is bound, like in `format!`. Like `write!`, the
+/// template can also be written into a destination with `render!(dst, "...")`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let xs = vec![1, 2, 3];
+/// let rendered = temply::render!("{% for x in xs.iter() %}{{ x }},{% endfor %}{{ y }}", y = 4);
+/// assert_eq!(rendered, "1,2,3,4");
+/// ```
+#[proc_macro]
+pub fn render(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    render::expand(input.into(), false)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Write an inline template into a destination, like `write!`.
+///
+/// The destination is either a `fmt::Write` or an `io::Write` and the trait must be in scope.
+/// Variables and named arguments are handled like in [`render!`](macro@render).
+///
+/// # Examples
+///
+/// ```ignore
+/// use std::fmt::Write;
+///
+/// let mut buffer = String::new();
+/// temply::write!(buffer, "{% for x in xs %}{{ x }},{% endfor %}", xs = &[1, 2, 3])?;
+/// assert_eq!(buffer, "1,2,3,");
+/// ```
+#[proc_macro]
+pub fn write(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    render::expand(input.into(), true)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+fn derive(input: TokenStream) -> syn::Result<TokenStream> {
     // Parse derive
-    let (name, data, generics, source, dedent) = parse_derive(input.into());
-
-    // Get source
-    let (source, path) = match source {
-        Source::File(path) => (
-            fs::read_to_string(&path).expect("failed to read template from file"),
-            Some(path),
-        ),
-        Source::Inline(source) => (source, None),
+    let (name, data, generics, attrs) = parse_derive(input)?;
+
+    // Compile the named templates
+    let mut paths = Vec::new();
+    let mut methods = Vec::new();
+    let mut default = match attrs.source {
+        Some(source) => Some(compile(source, attrs.dedent, &attrs.options, &mut paths)?),
+        None => None,
     };
+    for named in attrs.named {
+        syn::parse_str::<Ident>(&format!("render_{}", named.name.value()))
+            .map_err(|_| syn::Error::new(named.name.span(), "invalid template name"))?;
+        let options = named.options.or(&attrs.options);
+        let compiled = compile(named.source, attrs.dedent, &options, &mut paths)?;
+        if let Some(ident) = named.default {
+            if default.is_some() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "found more than one default template",
+                ));
+            }
+            default = Some(compiled.clone());
+        }
+        methods.push((named.name.value(), compiled.body, compiled.size_hint));
+    }
 
-    // Lex and parse
-    let tokens = lexer::lex(&source);
-    let mut ast = match parser::parse(&source, &tokens) {
-        Ok(ast) => ast,
-        Err(error) => panic!("failed to parse template: {}", error.format(&source)),
+    // Compile the templates of the variants, if any, and the default template of the type
+    let variants = match &data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| Ok((variant, parse_attrs(&variant.attrs)?)))
+            .collect::<syn::Result<Vec<_>>>()?,
+        Data::Struct(_) | Data::Union(_) => Vec::new(),
     };
+    let compiled = if variants.iter().any(|(_, v)| v.source.is_some()) {
+        let fallback = default;
+        let mut size_hint = fallback.as_ref().map_or(0, |fallback| fallback.size_hint);
+        let mut escape = fallback
+            .as_ref()
+            .map_or(Escape::Html, |fallback| fallback.escape);
+        let mut bodies = Vec::new();
+        for (variant, v) in variants {
+            if let Some(named) = v.named.first() {
+                return Err(syn::Error::new(
+                    named.name.span(),
+                    "named templates are only supported on the type",
+                ));
+            }
+            if let Some(display) = v.display {
+                return Err(syn::Error::new(
+                    display.span(),
+                    "`display` is only supported on the type",
+                ));
+            }
+            if let (None, Some(attr)) = (&v.source, v.options_attr) {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "options of a variant require a template source on the variant",
+                ));
+            }
+            let body =
+                match v.source {
+                    Some(source) => {
+                        let dedent = attrs.dedent || v.dedent;
+                        let options = v.options.or(&attrs.options);
+                        let compiled = compile(source, dedent, &options, &mut paths)?;
+                        size_hint = size_hint.max(compiled.size_hint);
+                        if compiled.escape != Escape::Html {
+                            escape = Escape::None;
+                        }
+                        Some(compiled.body)
+                    }
+                    None if fallback.is_some() => None,
+                    None => return Err(syn::Error::new(
+                        variant.ident.span(),
+                        "missing template source for variant, add one to the variant or the enum",
+                    )),
+                };
+            bodies.push((variant, body));
+        }
+        Compiled {
+            body: generator::generate_variants(bodies, fallback.map(|fallback| fallback.body)),
+            size_hint,
+            escape,
+        }
+    } else if let Some(default) = default {
+        default
+    } else if !methods.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            "missing default template, add `default` to one of the named templates",
+        ));
+    } else {
+        return Err(syn::Error::new(
+            name.span(),
+            "missing template source, add `#[template = \"...\"]` or `#[template_inline = \"...\"]`",
+        ));
+    };
+
+    // Generate
+    let display = attrs
+        .display
+        .map(|_| generator::generate_display(&name, &generics, compiled.escape));
+    let template = generator::generate(
+        &name,
+        &data,
+        &generics,
+        &paths,
+        compiled.body,
+        compiled.size_hint,
+        methods,
+    );
+    Ok(quote! {
+        #template
+        #display
+    })
+}
 
-    // Dedent and trim
-    if dedent {
-        ws::dedent(&mut ast);
+/// A compiled template source.
+#[derive(Clone)]
+struct Compiled {
+    body: TokenStream,
+    size_hint: usize,
+    /// The escape mode, the output of html-escaped templates is safe
+    escape: Escape,
+}
+
+/// Compiles a template source. The paths of all loaded files are added to `paths`.
+fn compile(
+    source: Source,
+    dedent: bool,
+    options: &Options,
+    paths: &mut Vec<PathBuf>,
+) -> syn::Result<Compiled> {
+    // Escape html by default for html files
+    let escape = options.escape.unwrap_or_else(|| match &source {
+        Source::File(path, _) if path.extension().map_or(false, |ext| ext == "html") => {
+            Escape::Html
+        }
+        _ => Escape::None,
+    });
+
+    // Compile
+    let lit = match &source {
+        Source::File(_, lit) | Source::Inline(lit) => lit.clone(),
+    };
+    let ctx = Context {
+        root_path: root_path(),
+        dedent,
+        escape,
+        filters: options
+            .filters
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(::temply::filters)),
+        syntax: options.syntax.clone().unwrap_or_default(),
+        lit,
+        sources: RefCell::new(Vec::new()),
+        includes: RefCell::new(Vec::new()),
+        compiled_includes: RefCell::new(HashMap::new()),
+    };
+    let source = match source {
+        Source::File(path, _) => {
+            ctx.includes.borrow_mut().push(canonicalize(&path));
+            ctx.read(path)
+                .map_err(|error| syn::Error::new(ctx.lit.span(), error))?
+        }
+        Source::Inline(lit) => {
+            let source = Rc::<str>::from(lit.value());
+            ctx.sources.borrow_mut().push((None, source.clone()));
+            source
+        }
+    };
+    let (mut body, size_hint) = ctx.compile(&source)?;
+
+    // The output of html-escaped templates is safe, which prevents escaping it twice if it is
+    // written into another html-escaped template
+    if escape == Escape::Html {
+        body = quote! {
+            ::temply::__intern::escape::raw(|| -> ::std::fmt::Result {
+                #body
+                Ok(())
+            })?;
+        };
     }
-    ws::trim(&mut ast);
 
-    // Generate
-    generator::generate(&name, &data, &generics, path.as_deref(), ast).into()
+    paths.extend(
+        ctx.sources
+            .into_inner()
+            .into_iter()
+            .filter_map(|(path, _)| path),
+    );
+    Ok(Compiled {
+        body,
+        size_hint,
+        escape,
+    })
 }
 
-#[derive(Debug)]
-enum Source {
-    File(PathBuf),
-    Inline(String),
-}
-
-fn parse_derive(input: TokenStream) -> (Ident, Data, Generics, Source, bool) {
-    let ast = syn::parse2::<DeriveInput>(input).unwrap();
-
-    let root_path =
-        Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string())).join("src/");
-    let sources = ast
-        .attrs
-        .iter()
-        .filter_map(|attr| match attr.parse_meta() {
-            Ok(Meta::NameValue(name_value)) => {
-                if name_value.path.is_ident("template") {
-                    match name_value.lit {
-                        Lit::Str(str) => Some(Source::File(root_path.join(str.value()))),
-                        _ => panic!("template must be a string"),
+struct Context {
+    root_path: PathBuf,
+    dedent: bool,
+    escape: Escape,
+    filters: syn::Path,
+    syntax: Syntax,
+    /// The literal of the template attribute, errors are reported at it
+    lit: LitStr,
+    /// All loaded sources with their path, `None` for the inline template
+    sources: RefCell<Vec<(Option<PathBuf>, Rc<str>)>>,
+    includes: RefCell<Vec<PathBuf>>,
+    /// Compiled includes with their size hint, so that repeated includes are compiled once
+    compiled_includes: RefCell<HashMap<PathBuf, (TokenStream, usize)>>,
+}
+
+impl Context {
+    /// Loads the template at `path`, which is a part of a loaded source.
+    fn load(&self, path: &str) -> syn::Result<Rc<str>> {
+        self.read(self.root_path.join(path))
+            .map_err(|error| self.error(path, &error.to_string()))
+    }
+
+    fn read(&self, path: PathBuf) -> Result<Rc<str>, ReadError> {
+        match fs::read_to_string(&path) {
+            Ok(source) => {
+                let source = Rc::<str>::from(source);
+                self.sources.borrow_mut().push((Some(path), source.clone()));
+                Ok(source)
+            }
+            Err(error) => Err(ReadError { path, error }),
+        }
+    }
+
+    fn parse<'s>(&self, source: &'s str) -> syn::Result<parser::ast::Ast<'s>> {
+        let tokens = lexer::lex(source, &self.syntax);
+        parser::parse(source, &tokens)
+            .map_err(|error| self.error(&source[error.span().range()], &error.message(source)))
+    }
+
+    /// Creates an error at `at`, which is a part of a loaded source.
+    fn error(&self, at: &str, message: &str) -> syn::Error {
+        let sources = self.sources.borrow();
+        let found = sources.iter().find_map(|(path, source)| {
+            let start = (at.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+            if start + at.len() <= source.len() {
+                Some((path, source, start..start + at.len()))
+            } else {
+                None
+            }
+        });
+
+        match found {
+            Some((Some(path), source, range)) => syn::Error::new(
+                self.lit.span(),
+                format!(
+                    "{}:{}: {}",
+                    path.display(),
+                    location(source, range.start),
+                    message
+                ),
+            ),
+            Some((None, source, range)) => syn::Error::new(
+                subspan(&self.lit, range.clone()).unwrap_or_else(|| self.lit.span()),
+                format!("{}: {}", location(source, range.start), message),
+            ),
+            None => syn::Error::new(self.lit.span(), message),
+        }
+    }
+
+    fn compile(&self, source: &str) -> syn::Result<(TokenStream, usize)> {
+        // Lex and parse
+        let ast = self.parse(source)?;
+
+        // Resolve extends
+        extends::resolve(ast, self, |mut ast| {
+            // Dedent and trim
+            if self.dedent {
+                ws::dedent(&mut ast);
+            }
+            ws::trim(&mut ast);
+            ws::indent(&mut ast);
+
+            // Generate
+            let size_hint = generator::size_hint(&ast, &|path| {
+                self.include(path).map_or(0, |(_, size_hint)| size_hint)
+            });
+            Ok((self.generate(ast, None)?, size_hint))
+        })
+    }
+
+    fn generate(
+        &self,
+        ast: parser::ast::Ast<'_>,
+        namespace: Option<&str>,
+    ) -> syn::Result<TokenStream> {
+        let ctx = generator::Context {
+            escape: self.escape,
+            filters: &self.filters,
+            include: &|path| self.include(path).map(|(body, _)| body),
+            import: &|path, namespace| self.import(path, namespace),
+            namespace,
+            error: &|at, message| self.error(at, message),
+        };
+        generator::generate_ast(ast, &ctx)
+    }
+
+    fn include(&self, path: &str) -> syn::Result<(TokenStream, usize)> {
+        let full_path = canonicalize(&self.root_path.join(path));
+        self.check_cycle(path, &full_path)?;
+
+        if let Some(compiled) = self.compiled_includes.borrow().get(&full_path) {
+            return Ok(compiled.clone());
+        }
+
+        self.includes.borrow_mut().push(full_path.clone());
+        let source = self.load(path)?;
+        let compiled = self.compile(&source)?;
+        self.includes.borrow_mut().pop();
+
+        self.compiled_includes
+            .borrow_mut()
+            .insert(full_path, compiled.clone());
+        Ok(compiled)
+    }
+
+    /// Generates the macros of the template at `path`, which may only contain macros.
+    fn import(&self, path: &str, namespace: Option<&str>) -> syn::Result<TokenStream> {
+        if let Some(namespace) = namespace {
+            syn::parse_str::<Ident>(namespace).map_err(|_| {
+                self.error(namespace, &format!("invalid namespace `{}`", namespace))
+            })?;
+        }
+
+        let full_path = canonicalize(&self.root_path.join(path));
+        self.check_cycle(path, &full_path)?;
+
+        self.includes.borrow_mut().push(full_path);
+        let source = self.load(path)?;
+        let mut ast = self.parse(&source)?;
+        let only_macros = ast.items.iter().all(|item| match item {
+            parser::ast::Item::Text(text) => {
+                text.lines.iter().all(|line| line.content.trim().is_empty())
+                    && text.trailing.trim().is_empty()
+            }
+            parser::ast::Item::Comment(_) | parser::ast::Item::Macro { .. } => true,
+            _ => false,
+        });
+        if !only_macros {
+            return Err(self.error(
+                path,
+                &format!("imported template `{}` may only contain macros", path),
+            ));
+        }
+        ast.items
+            .retain(|item| matches!(item, parser::ast::Item::Macro { .. }));
+        if self.dedent {
+            ws::dedent(&mut ast);
+        }
+        ws::trim(&mut ast);
+        ws::indent(&mut ast);
+        let body = self.generate(ast, namespace)?;
+        self.includes.borrow_mut().pop();
+
+        Ok(body)
+    }
+
+    /// Fails if `full_path` is already being included, imported or extended.
+    fn check_cycle(&self, path: &str, full_path: &Path) -> syn::Result<()> {
+        if self
+            .includes
+            .borrow()
+            .iter()
+            .any(|include| include == full_path)
+        {
+            let cycle = self
+                .includes
+                .borrow()
+                .iter()
+                .skip_while(|include| *include != full_path)
+                .map(|include| include.display().to_string())
+                .chain(Some(full_path.display().to_string()))
+                .collect::<Vec<_>>();
+            return Err(self.error(
+                path,
+                &format!("template cycle detected: {}", cycle.join(" -> ")),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+struct ReadError {
+    path: PathBuf,
+    error: io::Error,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        std::write!(
+            f,
+            "failed to read template from file `{}`: {}",
+            self.path.display(),
+            self.error
+        )
+    }
+}
+
+/// Returns the `line:column` of a byte offset in `source`.
+fn location(source: &str, offset: usize) -> String {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    format!("{}:{}", line, column)
+}
+
+/// Returns the span of a byte range of the value of `lit`. Only available on compilers which
+/// support subspans, otherwise `None` is returned.
+fn subspan(lit: &LitStr, range: Range<usize>) -> Option<proc_macro2::Span> {
+    // Map offsets of the value to offsets of the literal
+    let repr = lit.token().to_string();
+    let mut offsets = Vec::with_capacity(repr.len());
+    if repr.starts_with('r') {
+        let start = repr.find('"')? + 1;
+        offsets.extend(start..repr.len());
+    } else {
+        let mut chars = repr.char_indices().skip(1).peekable();
+        while let Some((idx, c)) = chars.next() {
+            let len = match c {
+                '"' => {
+                    offsets.push(idx);
+                    break;
+                }
+                '\\' => match chars.next()?.1 {
+                    'x' => {
+                        chars.nth(1)?;
+                        1
+                    }
+                    'u' => {
+                        let digits = chars
+                            .by_ref()
+                            .map(|(_, c)| c)
+                            .take_while(|&c| c != '}')
+                            .filter(char::is_ascii_hexdigit)
+                            .collect::<String>();
+                        char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?.len_utf8()
                     }
-                } else if name_value.path.is_ident("template_inline") {
-                    match name_value.lit {
-                        Lit::Str(str) => Some(Source::Inline(str.value())),
-                        _ => panic!("template_inline must be a string"),
+                    '\n' => {
+                        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+                        0
                     }
-                } else {
-                    None
+                    _ => 1,
+                },
+                c => c.len_utf8(),
+            };
+            offsets.extend((0..len).map(|_| idx));
+        }
+    }
+
+    let start = *offsets.get(range.start)?;
+    let end = *offsets.get(range.end)?;
+    lit.token().subspan(start..end)
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn root_path() -> PathBuf {
+    Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string())).join("src/")
+}
+
+enum Source {
+    File(PathBuf, LitStr),
+    Inline(LitStr),
+}
+
+#[derive(Default)]
+struct Options {
+    escape: Option<Escape>,
+    filters: Option<syn::Path>,
+    syntax: Option<Syntax>,
+}
+
+impl Options {
+    /// Fills the options that are not set with the options of `other`.
+    fn or(self, other: &Options) -> Options {
+        Options {
+            escape: self.escape.or(other.escape),
+            filters: self.filters.or_else(|| other.filters.clone()),
+            syntax: self.syntax.or_else(|| other.syntax.clone()),
+        }
+    }
+}
+
+enum TemplateOption {
+    Escape(Escape),
+    Filters(syn::Path),
+    Name(LitStr),
+    Path(LitStr),
+    Inline(LitStr),
+    Default(Ident),
+    Display(Ident),
+    Syntax(Syntax),
+}
+
+impl Parse for TemplateOption {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let name = input.parse::<Ident>()?;
+        if name == "default" {
+            return Ok(Self::Default(name));
+        } else if name == "display" {
+            return Ok(Self::Display(name));
+        } else if name == "syntax" {
+            return parse_syntax(name, input).map(Self::Syntax);
+        }
+        input.parse::<Token![=]>()?;
+
+        if name == "name" {
+            Ok(Self::Name(input.parse()?))
+        } else if name == "path" {
+            Ok(Self::Path(input.parse()?))
+        } else if name == "inline" {
+            Ok(Self::Inline(input.parse()?))
+        } else if name == "escape" {
+            let escape = input.parse::<LitStr>()?;
+            match escape.value().as_str() {
+                "html" => Ok(Self::Escape(Escape::Html)),
+                "none" => Ok(Self::Escape(Escape::None)),
+                _ => Err(syn::Error::new(
+                    escape.span(),
+                    "escape must be one of \"html\", \"none\"",
+                )),
+            }
+        } else if name == "filters" {
+            Ok(Self::Filters(input.parse()?))
+        } else {
+            Err(syn::Error::new(name.span(), "unknown template option"))
+        }
+    }
+}
+
+/// Parses `syntax(expr = ("{{", "}}"), block = ("{%", "%}"), comment = ("{#", "#}"))`, all
+/// delimiters are optional.
+fn parse_syntax(name: Ident, input: ParseStream<'_>) -> syn::Result<Syntax> {
+    let content;
+    syn::parenthesized!(content in input);
+
+    let mut syntax = Syntax::default();
+    while !content.is_empty() {
+        let kind = content.parse::<Ident>()?;
+        content.parse::<Token![=]>()?;
+        let delimiters;
+        syn::parenthesized!(delimiters in content);
+        let start = delimiters.parse::<LitStr>()?.value();
+        delimiters.parse::<Token![,]>()?;
+        let end = delimiters.parse::<LitStr>()?.value();
+
+        if kind == "expr" {
+            syntax.expr = (start, end);
+        } else if kind == "block" {
+            syntax.block = (start, end);
+        } else if kind == "comment" {
+            syntax.comment = (start, end);
+        } else {
+            return Err(syn::Error::new(
+                kind.span(),
+                "unknown delimiter, expected one of `expr`, `block`, `comment`",
+            ));
+        }
+
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+
+    let delimiters = syntax.delimiters();
+    for (idx, (delimiter, _)) in delimiters.iter().enumerate() {
+        if delimiter.is_empty()
+            || delimiters[..idx]
+                .iter()
+                .any(|(other, _)| other == delimiter)
+        {
+            return Err(syn::Error::new(
+                name.span(),
+                "delimiters must be non-empty and distinct",
+            ));
+        }
+    }
+
+    Ok(syntax)
+}
+
+struct Attrs {
+    source: Option<Source>,
+    named: Vec<Named>,
+    dedent: bool,
+    display: Option<Ident>,
+    options: Options,
+    /// The first attribute setting `dedent` or an option, which variants only support with a source
+    options_attr: Option<Attribute>,
+}
+
+/// A template given by `#[template(name = "...", path = "...")]`.
+struct Named {
+    name: LitStr,
+    source: Source,
+    default: Option<Ident>,
+    options: Options,
+}
+
+fn parse_derive(input: TokenStream) -> syn::Result<(Ident, Data, Generics, Attrs)> {
+    let ast = syn::parse2::<DeriveInput>(input)?;
+    let attrs = parse_attrs(&ast.attrs)?;
+
+    Ok((ast.ident, ast.data, ast.generics, attrs))
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> syn::Result<Attrs> {
+    let root_path = root_path();
+    let mut source = None;
+    for attr in attrs {
+        let name_value = match attr.parse_meta() {
+            Ok(Meta::NameValue(name_value)) => name_value,
+            _ => continue,
+        };
+        let parsed = if name_value.path.is_ident("template") {
+            match name_value.lit {
+                Lit::Str(str) => Source::File(root_path.join(str.value()), str),
+                lit => return Err(syn::Error::new_spanned(lit, "template must be a string")),
+            }
+        } else if name_value.path.is_ident("template_inline") {
+            match name_value.lit {
+                Lit::Str(str) => Source::Inline(str),
+                lit => {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "template_inline must be a string",
+                    ))
                 }
             }
-            _ => None,
-        })
-        .collect::<Vec<_>>();
-    let source = if sources.len() == 1 {
-        sources.into_iter().next().unwrap()
-    } else {
-        panic!("found zero or more than one template source");
-    };
-    let dedent = ast.attrs.iter().any(|attr| match attr.parse_meta() {
+        } else {
+            continue;
+        };
+        if source.replace(parsed).is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "found more than one template source",
+            ));
+        }
+    }
+    let dedent = attrs.iter().find(|attr| match attr.parse_meta() {
         Ok(Meta::Path(p)) => p.is_ident("dedent"),
         _ => false,
     });
+    let mut options_attr = dedent.cloned();
+
+    let mut named = Vec::<Named>::new();
+    let mut display = None;
+    let mut options = Options::default();
+    for attr in attrs {
+        let is_list = matches!(
+            attr.tokens.clone().into_iter().next(),
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis
+        );
+        if !attr.path.is_ident("template") || !is_list {
+            continue;
+        }
+
+        let parsed =
+            attr.parse_args_with(Punctuated::<TemplateOption, Token![,]>::parse_terminated)?;
+        let mut name = None;
+        let mut source = None;
+        let mut default = None;
+        let mut attr_display = None;
+        let mut attr_options = Options::default();
+        for option in parsed {
+            match option {
+                TemplateOption::Escape(escape) => attr_options.escape = Some(escape),
+                TemplateOption::Filters(filters) => attr_options.filters = Some(filters),
+                TemplateOption::Syntax(syntax) => attr_options.syntax = Some(syntax),
+                TemplateOption::Name(lit) => name = Some(lit),
+                TemplateOption::Path(lit) => {
+                    source = Some(Source::File(root_path.join(lit.value()), lit))
+                }
+                TemplateOption::Inline(lit) => source = Some(Source::Inline(lit)),
+                TemplateOption::Default(ident) => default = Some(ident),
+                TemplateOption::Display(ident) => attr_display = Some(ident),
+            }
+        }
+
+        match name {
+            Some(name) => {
+                if let Some(display) = attr_display {
+                    return Err(syn::Error::new(
+                        display.span(),
+                        "`display` is only supported for the default template",
+                    ));
+                }
+                if named.iter().any(|named| named.name.value() == name.value()) {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "found more than one template with this name",
+                    ));
+                }
+                let source = source.ok_or_else(|| {
+                    syn::Error::new(
+                        name.span(),
+                        "missing template source, add `path` or `inline`",
+                    )
+                })?;
+                named.push(Named {
+                    name,
+                    source,
+                    default,
+                    options: attr_options,
+                });
+            }
+            None if source.is_some() || default.is_some() => {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`path`, `inline` and `default` require a `name`",
+                ));
+            }
+            None => {
+                if attr_options.escape.is_some()
+                    || attr_options.filters.is_some()
+                    || attr_options.syntax.is_some()
+                {
+                    options_attr = options_attr.or_else(|| Some(attr.clone()));
+                }
+                options = attr_options.or(&options);
+                display = display.or(attr_display);
+            }
+        }
+    }
 
-    (ast.ident, ast.data, ast.generics, source, dedent)
+    Ok(Attrs {
+        source,
+        named,
+        dedent: dedent.is_some(),
+        display,
+        options,
+        options_attr,
+    })
 }
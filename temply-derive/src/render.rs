@@ -0,0 +1,112 @@
+use crate::{Options, Source};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Expr, ExprLit, Ident, Lit, LitStr, Token};
+
+/// The input of `render!` and `write!`: an optional destination, the template and named arguments.
+struct Input {
+    dst: Option<Expr>,
+    template: LitStr,
+    args: Vec<(Ident, Expr)>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let (dst, template) = match input.parse::<Expr>()? {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(template),
+                ..
+            }) => (None, template),
+            dst => {
+                input.parse::<Token![,]>()?;
+                (Some(dst), input.parse()?)
+            }
+        };
+
+        let mut args = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            args.push((name, input.parse()?));
+        }
+
+        Ok(Self {
+            dst,
+            template,
+            args,
+        })
+    }
+}
+
+/// Expands `render!` and `write!`. Without a destination, the template is rendered to a string,
+/// which is only allowed if `dst_required` is false.
+pub fn expand(input: TokenStream, dst_required: bool) -> syn::Result<TokenStream> {
+    let Input {
+        dst,
+        template,
+        args,
+    } = syn::parse2(input)?;
+    if dst_required && dst.is_none() {
+        return Err(syn::Error::new(
+            template.span(),
+            "missing destination, use `write!(dst, \"...\")`",
+        ));
+    }
+
+    let mut paths = Vec::new();
+    let compiled = crate::compile(
+        Source::Inline(template),
+        false,
+        &Options::default(),
+        &mut paths,
+    )?;
+
+    // Recompile if an included template changes
+    let recompile_on_change = paths.iter().map(|path| {
+        let path = path.to_str().unwrap();
+        quote! {
+            const _: &str = include_str!(#path);
+        }
+    });
+    let names = args.iter().map(|(name, _)| name);
+    let values = args.iter().map(|(_, value)| value);
+    let body = compiled.body;
+    let render = quote! {
+        ::temply::__intern::render::Render::new(|__buffer: &mut dyn ::std::fmt::Write| -> ::std::fmt::Result {
+            #body
+            Ok(())
+        })
+    };
+    let write = match dst {
+        // The destination is parenthesized, so that e.g. `&mut buffer` is not applied to the result
+        Some(dst) => quote! {
+            (#dst).write_fmt(::std::format_args!("{}", #render))
+        },
+        None => {
+            let size_hint = compiled.size_hint;
+            quote! {
+                let mut __string = ::std::string::String::with_capacity(#size_hint);
+                ::std::fmt::Write::write_fmt(&mut __string, ::std::format_args!("{}", #render))
+                    .expect("a Display implementation returned an error unexpectedly");
+                __string
+            }
+        }
+    };
+
+    // All values are evaluated before any name is bound, like in `format!`
+    Ok(quote! {
+        {
+            #(#recompile_on_change)*
+            match (#(#values,)*) {
+                (#(#names,)*) => {
+                    #write
+                }
+            }
+        }
+    })
+}
@@ -1,5 +0,0 @@
-mod dedent;
-mod trim;
-mod util;
-
-pub use self::{dedent::dedent, trim::trim};
@@ -0,0 +1,654 @@
+mod span;
+mod syntax;
+mod token;
+
+pub use self::{
+    span::{Span, Spanned},
+    syntax::Syntax,
+    token::Token,
+};
+
+/// Splits `source` into tokens. All spans are byte offsets into `source`.
+pub fn lex(source: &str, syntax: &Syntax) -> Vec<Spanned<Token>> {
+    let delimiters = syntax.delimiters();
+    let mut pos_start = 0;
+    let mut pos_current = 0;
+
+    let mut tokens = Vec::new();
+
+    while let Some(c) = source[pos_current..].chars().next() {
+        // Find the longest delimiter at the current position
+        let rest = &source[pos_current..];
+        let delimiter = delimiters
+            .iter()
+            .filter(|(delimiter, _)| rest.starts_with(delimiter))
+            .max_by_key(|(delimiter, _)| delimiter.len());
+
+        match delimiter {
+            Some((delimiter, token)) => {
+                if pos_start < pos_current {
+                    tokens.push(Spanned {
+                        node: Token::Other,
+                        span: (pos_start..pos_current).into(),
+                    });
+                }
+                tokens.push(Spanned {
+                    node: *token,
+                    span: (pos_current..pos_current + delimiter.len()).into(),
+                });
+                pos_current += delimiter.len();
+                pos_start = pos_current;
+
+                // The content of a raw block is text up to the matching endraw tag
+                if *token == Token::BlockStart {
+                    if let Some(len) = tag_len(&source[pos_current..], "raw", syntax) {
+                        let end = pos_current + len - syntax.block.1.len();
+                        tokens.push(Spanned {
+                            node: Token::Other,
+                            span: (pos_current..end).into(),
+                        });
+                        tokens.push(Spanned {
+                            node: Token::BlockEnd,
+                            span: (end..pos_current + len).into(),
+                        });
+                        pos_start = pos_current + len;
+                        pos_current = find_endraw(source, pos_start, syntax);
+                    }
+                }
+            }
+            None => pos_current += c.len_utf8(),
+        }
+    }
+
+    if pos_start < source.len() {
+        tokens.push(Spanned {
+            node: Token::Other,
+            span: (pos_start..source.len()).into(),
+        });
+    }
+
+    trim_markers(source, &mut tokens);
+
+    tokens
+}
+
+/// Returns the length of the block tag `name` up to and including its end delimiter, if `rest`
+/// starts with it. `rest` starts right after the start delimiter.
+fn tag_len(rest: &str, name: &str, syntax: &Syntax) -> Option<usize> {
+    let content = if starts_with_marker(rest) {
+        &rest[1..]
+    } else {
+        rest
+    };
+    let content = content.trim_start().strip_prefix(name)?;
+    let trimmed = content.trim_start();
+    let content = trimmed.strip_prefix(syntax.block.1.as_str()).or_else(|| {
+        if trimmed.len() == content.len() {
+            return None;
+        }
+        trimmed
+            .strip_prefix('-')?
+            .strip_prefix(syntax.block.1.as_str())
+    })?;
+    Some(rest.len() - content.len())
+}
+
+/// Returns the position of the endraw tag closing a raw block whose content starts at `pos`, or
+/// the end of `source` if there is none.
+fn find_endraw(source: &str, pos: usize, syntax: &Syntax) -> usize {
+    let start = syntax.block.0.as_str();
+    source[pos..]
+        .match_indices(start)
+        .map(|(idx, _)| pos + idx)
+        .find(|&idx| tag_len(&source[idx + start.len()..], "endraw", syntax).is_some())
+        .unwrap_or(source.len())
+}
+
+/// Moves the whitespace control markers `-` into the spans of the adjacent tags and trims the
+/// whitespace on the marked side of these tags. A `-` is only a marker if it is separated from the
+/// content of the tag by whitespace, so that e.g. `{{-1}}` and `{#-- a --#}` are not trimmed.
+fn trim_markers(source: &str, tokens: &mut Vec<Spanned<Token>>) {
+    let is_other = |t: &Spanned<Token>| t.node == Token::Other;
+
+    // Move markers into tags
+    let mut marked = vec![false; tokens.len()];
+    for idx in 0..tokens.len() {
+        match tokens[idx].node {
+            Token::ExprStart | Token::BlockStart | Token::CommentStart => {
+                if let Some(next) = tokens.get(idx + 1).filter(|t| is_other(t)) {
+                    if starts_with_marker(&source[next.span.range()]) {
+                        tokens[idx].span.end += 1;
+                        tokens[idx + 1].span.start += 1;
+                        marked[idx] = true;
+                    }
+                }
+            }
+            Token::ExprEnd | Token::BlockEnd | Token::CommentEnd => {
+                if let Some(prev) = idx.checked_sub(1).map(|idx| &tokens[idx]) {
+                    if is_other(prev) && ends_with_marker(&source[prev.span.range()]) {
+                        tokens[idx].span.start -= 1;
+                        tokens[idx - 1].span.end -= 1;
+                        marked[idx] = true;
+                    }
+                }
+            }
+            Token::Other => (),
+        }
+    }
+
+    // Trim whitespace
+    for idx in 0..tokens.len() {
+        let is_marked = marked[idx];
+        match tokens[idx].node {
+            Token::ExprStart | Token::BlockStart | Token::CommentStart if is_marked => {
+                if let Some(prev) = idx.checked_sub(1).map(|idx| &mut tokens[idx]) {
+                    if prev.node == Token::Other {
+                        let len = source[prev.span.range()].trim_end().len();
+                        prev.span.end = prev.span.start + len;
+                    }
+                }
+            }
+            Token::ExprEnd | Token::BlockEnd | Token::CommentEnd if is_marked => {
+                if let Some(next) = tokens.get_mut(idx + 1) {
+                    if next.node == Token::Other {
+                        let len = source[next.span.range()].trim_start().len();
+                        next.span.start = next.span.end - len;
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Remove empty text
+    tokens.retain(|t| !is_other(t) || t.span.start < t.span.end);
+}
+
+/// Returns whether `content` starts with a `-` followed by whitespace or nothing.
+fn starts_with_marker(content: &str) -> bool {
+    content.strip_prefix('-').map_or(false, |rest| {
+        rest.is_empty() || rest.starts_with(char::is_whitespace)
+    })
+}
+
+/// Returns whether `content` ends with a `-` preceded by whitespace or nothing.
+fn ends_with_marker(content: &str) -> bool {
+    content.strip_suffix('-').map_or(false, |rest| {
+        rest.is_empty() || rest.ends_with(char::is_whitespace)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expr() {
+        let source = "{{ x }} }} {{{{  { {  {\n{ } } }\n} }}";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 0, end: 2 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 2, end: 5 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 5, end: 7 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 7, end: 8 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 8, end: 10 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 10, end: 11 }
+                },
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 11, end: 13 }
+                },
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 13, end: 15 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 15, end: 34 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 34, end: 36 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block() {
+        let source = "{% x %} %} {%{%  { {  {\n{ } } }\n} %}";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 0, end: 2 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 2, end: 5 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 5, end: 7 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 7, end: 8 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 8, end: 10 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 10, end: 11 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 11, end: 13 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 13, end: 15 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 15, end: 34 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 34, end: 36 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comment() {
+        let source = "{# x #} #} {#{#  { {  {\n{ } } }\n} #}";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::CommentStart,
+                    span: Span { start: 0, end: 2 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 2, end: 5 }
+                },
+                Spanned {
+                    node: Token::CommentEnd,
+                    span: Span { start: 5, end: 7 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 7, end: 8 }
+                },
+                Spanned {
+                    node: Token::CommentEnd,
+                    span: Span { start: 8, end: 10 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 10, end: 11 }
+                },
+                Spanned {
+                    node: Token::CommentStart,
+                    span: Span { start: 11, end: 13 }
+                },
+                Spanned {
+                    node: Token::CommentStart,
+                    span: Span { start: 13, end: 15 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 15, end: 34 }
+                },
+                Spanned {
+                    node: Token::CommentEnd,
+                    span: Span { start: 34, end: 36 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_misc() {
+        let source = "#}\n\t\t\t   Hello{#World Test{{ %} {{ {% }} {:#?} {} []";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::CommentEnd,
+                    span: Span { start: 0, end: 2 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 2, end: 14 }
+                },
+                Spanned {
+                    node: Token::CommentStart,
+                    span: Span { start: 14, end: 16 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 16, end: 26 }
+                },
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 26, end: 28 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 28, end: 29 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 29, end: 31 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 31, end: 32 }
+                },
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 32, end: 34 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 34, end: 35 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 35, end: 37 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 37, end: 38 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 38, end: 40 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 40, end: 52 }
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markers() {
+        let source = "a \n{{- x -}}\n b{%-%} c {#- -#}-}}";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 0, end: 1 }
+                },
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 3, end: 6 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 6, end: 9 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 9, end: 12 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 14, end: 15 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 15, end: 18 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 18, end: 20 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 20, end: 22 }
+                },
+                Spanned {
+                    node: Token::CommentStart,
+                    span: Span { start: 23, end: 26 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 26, end: 27 }
+                },
+                Spanned {
+                    node: Token::CommentEnd,
+                    span: Span { start: 27, end: 30 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 30, end: 33 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markers_need_whitespace() {
+        let source = "{{-1}}{{ x-}}{#-- a --#}{%- raw-%}";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|t| &source[t.span.range()])
+                .collect::<Vec<_>>(),
+            vec!["{{", "-1", "}}", "{{", " x-", "}}", "{#", "-- a --", "#}", "{%-", " raw-", "%}"]
+        );
+    }
+
+    #[test]
+    fn test_unicode() {
+        let source = "ä{{ ö }}€{%- 🦀 -%} ─";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 0, end: 2 }
+                },
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 2, end: 4 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 4, end: 8 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 8, end: 10 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 10, end: 13 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 13, end: 16 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 16, end: 22 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 22, end: 25 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 26, end: 29 }
+                },
+            ]
+        );
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|t| &source[t.span.range()])
+                .collect::<Vec<_>>(),
+            vec!["ä", "{{", " ö ", "}}", "€", "{%-", " 🦀 ", "-%}", "─"]
+        );
+    }
+
+    #[test]
+    fn test_syntax() {
+        let syntax = Syntax {
+            expr: ("<<".to_string(), ">>".to_string()),
+            block: ("<%".to_string(), "%>".to_string()),
+            comment: ("<<#".to_string(), "#>>".to_string()),
+        };
+        let source = "{{ a }} <<- b >><<# c #>> <%- d%>";
+        let tokens = lex(source, &syntax);
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 0, end: 7 }
+                },
+                Spanned {
+                    node: Token::ExprStart,
+                    span: Span { start: 8, end: 11 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 11, end: 14 }
+                },
+                Spanned {
+                    node: Token::ExprEnd,
+                    span: Span { start: 14, end: 16 }
+                },
+                Spanned {
+                    node: Token::CommentStart,
+                    span: Span { start: 16, end: 19 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 19, end: 22 }
+                },
+                Spanned {
+                    node: Token::CommentEnd,
+                    span: Span { start: 22, end: 25 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 26, end: 29 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 29, end: 31 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 31, end: 33 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw() {
+        let source = "{%- raw %}{{ {% #}{% endraw -%} {%raw%}{%endraw%}";
+        let tokens = lex(source, &Syntax::default());
+        assert_eq!(
+            tokens,
+            vec![
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 0, end: 3 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 3, end: 8 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 8, end: 10 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 10, end: 18 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 18, end: 20 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 20, end: 28 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 28, end: 31 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 32, end: 34 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 34, end: 37 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 37, end: 39 }
+                },
+                Spanned {
+                    node: Token::BlockStart,
+                    span: Span { start: 39, end: 41 }
+                },
+                Spanned {
+                    node: Token::Other,
+                    span: Span { start: 41, end: 47 }
+                },
+                Spanned {
+                    node: Token::BlockEnd,
+                    span: Span { start: 47, end: 49 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(lex("", &Syntax::default()), vec![]);
+    }
+}
@@ -0,0 +1,33 @@
+use super::Token;
+
+/// The delimiters of a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Syntax {
+    pub expr: (String, String),
+    pub block: (String, String),
+    pub comment: (String, String),
+}
+
+impl Syntax {
+    /// Returns all delimiters with their token.
+    pub fn delimiters(&self) -> [(&str, Token); 6] {
+        [
+            (&self.expr.0, Token::ExprStart),
+            (&self.expr.1, Token::ExprEnd),
+            (&self.block.0, Token::BlockStart),
+            (&self.block.1, Token::BlockEnd),
+            (&self.comment.0, Token::CommentStart),
+            (&self.comment.1, Token::CommentEnd),
+        ]
+    }
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Self {
+            expr: ("{{".to_string(), "}}".to_string()),
+            block: ("{%".to_string(), "%}".to_string()),
+            comment: ("{#".to_string(), "#}".to_string()),
+        }
+    }
+}
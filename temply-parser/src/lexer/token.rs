@@ -0,0 +1,19 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Token {
+    /// Start of an expression, `{{` or `{{-` by default
+    ExprStart,
+    /// End of an expression, `}}` or `-}}` by default
+    ExprEnd,
+
+    /// Start of a block, `{%` or `{%-` by default
+    BlockStart,
+    /// End of a block, `%}` or `-%}` by default
+    BlockEnd,
+
+    /// Start of a comment, `{#` or `{#-` by default
+    CommentStart,
+    /// End of a comment, `#}` or `-#}` by default
+    CommentEnd,
+
+    Other,
+}
@@ -0,0 +1,7 @@
+#![deny(rust_2018_idioms)]
+//! Lexer and parser of temply templates, shared by the derive and the dynamic engine of temply.
+//! This is an implementation detail of temply and not a stable api.
+
+pub mod lexer;
+pub mod parser;
+pub mod ws;
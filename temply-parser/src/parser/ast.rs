@@ -0,0 +1,95 @@
+#[derive(Debug, Clone)]
+pub struct Ast<'s> {
+    pub items: Vec<Item<'s>>,
+    pub items_indent: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Item<'s> {
+    Text(Text<'s>),
+    Comment(#[allow(dead_code)] &'s str),
+    Expr {
+        expr: &'s str,
+        filters: Vec<Filter<'s>>,
+        format: &'s str,
+        ind: usize,
+    },
+    Let(&'s str),
+    Use(&'s str),
+    Scope(Ast<'s>),
+    Indent {
+        indent: &'s str,
+        body: Ast<'s>,
+    },
+    Capture {
+        name: &'s str,
+        body: Ast<'s>,
+    },
+    For {
+        label: Option<&'s str>,
+        for_: &'s str,
+        pre: Option<Text<'s>>,
+        body: Ast<'s>,
+        else_: Option<Ast<'s>>,
+    },
+    While {
+        label: Option<&'s str>,
+        while_: &'s str,
+        body: Ast<'s>,
+    },
+    Break(&'s str),
+    Continue(&'s str),
+    If {
+        if_: (&'s str, Ast<'s>),
+        else_ifs: Vec<(&'s str, Ast<'s>)>,
+        else_: Option<Ast<'s>>,
+    },
+    Match {
+        match_: &'s str,
+        wheres: Vec<(&'s str, Ast<'s>)>,
+    },
+    Macro {
+        name: &'s str,
+        params: Vec<&'s str>,
+        body: Ast<'s>,
+    },
+    Call {
+        name: &'s str,
+        args: Vec<&'s str>,
+        caller: Option<Ast<'s>>,
+        ind: usize,
+    },
+    Caller {
+        caller: &'s str,
+        ind: usize,
+    },
+    Include(&'s str),
+    Import {
+        path: &'s str,
+        namespace: Option<&'s str>,
+    },
+    Extends(&'s str),
+    Block {
+        name: &'s str,
+        body: Ast<'s>,
+    },
+    Super(&'s str),
+}
+
+#[derive(Debug, Clone)]
+pub struct Filter<'s> {
+    pub name: &'s str,
+    pub args: Vec<&'s str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Text<'s> {
+    pub lines: Vec<Line<'s>>,
+    pub trailing: &'s str,
+}
+
+#[derive(Debug, Clone)]
+pub struct Line<'s> {
+    pub content: &'s str,
+    pub new_line: &'s str,
+}
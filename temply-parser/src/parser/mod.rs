@@ -0,0 +1,980 @@
+pub mod ast;
+
+use crate::lexer::{Span, Spanned, Token};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+#[derive(Debug)]
+pub struct Error {
+    span: Span,
+    token: Option<Token>,
+    expected: HashSet<Expected>,
+}
+
+impl Error {
+    fn new(span: Span, token: Option<Token>, expected: HashSet<Expected>) -> Self {
+        Self {
+            span,
+            token,
+            expected,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn message(&self, source: &str) -> String {
+        let mut buffer = String::new();
+
+        match self.token {
+            Some(_) => write!(&mut buffer, "unexpected `{}`", &source[self.span.range()]).unwrap(),
+            None => write!(&mut buffer, "unexpected end of template").unwrap(),
+        }
+        write!(&mut buffer, ", expected one of ").unwrap();
+
+        let mut expected = self.expected.iter().collect::<Vec<_>>();
+        expected.sort();
+        for (idx, expected) in expected.iter().enumerate() {
+            if idx != 0 {
+                write!(&mut buffer, ", ").unwrap();
+            }
+            match expected {
+                Expected::Block(filter) => {
+                    write!(&mut buffer, "{}-block", filter.name()).unwrap();
+                }
+                Expected::Token(token) => write!(&mut buffer, "`{:?}`", token).unwrap(),
+            }
+        }
+
+        buffer
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Expected {
+    Block(BlockFilter),
+    Token(Token),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum BlockFilter {
+    Equals(&'static str),
+    StartsWith(&'static str),
+    /// Starts with the keyword of a loop, which may be preceded by a label
+    Loop(&'static str),
+}
+
+impl BlockFilter {
+    fn name(&self) -> &'static str {
+        match self {
+            BlockFilter::Equals(filter) => filter,
+            BlockFilter::StartsWith(filter) => filter,
+            BlockFilter::Loop(filter) => filter,
+        }
+    }
+}
+
+pub type Result<'t, T> = std::result::Result<(&'t [Spanned<Token>], T), Error>;
+
+pub fn parse<'s>(
+    source: &'s str,
+    tokens: &[Spanned<Token>],
+) -> std::result::Result<ast::Ast<'s>, Error> {
+    let (tokens, ast) = parse_ast(source, tokens)?;
+
+    if tokens.is_empty() {
+        Ok(ast)
+    } else {
+        Err(parse_item(source, tokens).unwrap_err())
+    }
+}
+
+fn parse_ast<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Ast<'s>> {
+    parse_items(source, tokens, 0)
+}
+
+/// Parses items up to the end of the current block. `open_calls` is the number of calls around
+/// these items whose caller is closed by an `endcall` on the same level.
+fn parse_items<'s, 't>(
+    source: &'s str,
+    mut tokens: &'t [Spanned<Token>],
+    open_calls: usize,
+) -> Result<'t, ast::Ast<'s>> {
+    let mut items = Vec::new();
+
+    let tokens_before = tokens;
+
+    while let Ok((rest, mut item)) = parse_item(source, tokens) {
+        tokens = rest;
+
+        // Endcalls close the outermost calls first, so a call has a caller if there are more
+        // endcalls left on this level than the calls around it need
+        if let ast::Item::Call { caller, .. } = &mut item {
+            if count_endcalls(source, tokens) > open_calls {
+                let (rest, body) = parse_items(source, tokens, open_calls + 1)?;
+                let (rest, _) = parse_block(source, rest, BlockFilter::Equals("endcall"))?;
+                tokens = rest;
+                *caller = Some(body);
+            }
+        }
+
+        items.push(item);
+    }
+
+    let items_indent = if tokens_before.len() == tokens.len() {
+        None
+    } else {
+        let tokens_consumed = &tokens_before[0..tokens_before.len() - tokens.len()];
+        let first = tokens_consumed.first().unwrap().span;
+        let last = tokens_consumed.last().unwrap().span;
+        let source_consumed = &source[first.start..last.end];
+        indent(source_consumed)
+    };
+
+    Ok((
+        tokens,
+        ast::Ast {
+            items,
+            items_indent,
+        },
+    ))
+}
+
+fn parse_item<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    parse_text(source, tokens)
+        .alt(|| parse_comment(source, tokens))
+        .alt(|| parse_raw(source, tokens))
+        .alt(|| parse_expr(source, tokens))
+        .alt(|| parse_let(source, tokens))
+        .alt(|| parse_use(source, tokens))
+        .alt(|| parse_scope(source, tokens))
+        .alt(|| parse_indent(source, tokens))
+        .alt(|| parse_capture(source, tokens))
+        .alt(|| parse_for(source, tokens))
+        .alt(|| parse_while(source, tokens))
+        .alt(|| parse_break(source, tokens))
+        .alt(|| parse_continue(source, tokens))
+        .alt(|| parse_if(source, tokens))
+        .alt(|| parse_match(source, tokens))
+        .alt(|| parse_macro(source, tokens))
+        .alt(|| parse_caller(source, tokens))
+        .alt(|| parse_call(source, tokens))
+        .alt(|| parse_include(source, tokens))
+        .alt(|| parse_import(source, tokens))
+        .alt(|| parse_extends(source, tokens))
+        .alt(|| parse_named_block(source, tokens))
+        .alt(|| parse_super(source, tokens))
+}
+
+fn parse_text<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    let (rest, span) = exact(source, tokens, Token::Other)?;
+
+    let mut text = ast::Text {
+        lines: Vec::new(),
+        trailing: "",
+    };
+    for part in source[span.range()].split_inclusive('\n') {
+        let len = part.len();
+        if part.ends_with("\r\n") {
+            text.lines.push(ast::Line {
+                content: &part[..len - 2],
+                new_line: "\r\n",
+            });
+        } else if part.ends_with('\n') {
+            text.lines.push(ast::Line {
+                content: &part[..len - 1],
+                new_line: "\n",
+            });
+        } else {
+            text.trailing = part;
+        }
+    }
+
+    Ok((rest, ast::Item::Text(text)))
+}
+
+fn parse_comment<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    // Error
+    let unclosed_comment = || {
+        Error::new(
+            (source.len()..source.len()).into(),
+            None,
+            [Expected::Token(Token::CommentEnd)].into_iter().collect(),
+        )
+    };
+
+    // Start
+    let (mut tokens, _span) = exact(source, tokens, Token::CommentStart)?;
+    let mut depth = 1;
+    let span_start = tokens.first().ok_or_else(unclosed_comment)?.span.start;
+    let mut span_end = span_start;
+
+    // Body/End
+    while let Some(t) = tokens.first() {
+        match t.node {
+            Token::CommentStart => depth += 1,
+            Token::CommentEnd => depth -= 1,
+            _ => span_end = t.span.end,
+        }
+        tokens = &tokens[1..];
+
+        if depth == 0 {
+            return Ok((tokens, ast::Item::Comment(&source[span_start..span_end])));
+        }
+    }
+
+    Err(unclosed_comment())
+}
+
+fn parse_raw<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("raw"))?;
+
+    // Body, the lexer emits the whole content as a single text
+    let (tokens, text) = match parse_text(source, tokens) {
+        Ok((rest, text)) => (rest, text),
+        Err(_) => (
+            tokens,
+            ast::Item::Text(ast::Text {
+                lines: Vec::new(),
+                trailing: "",
+            }),
+        ),
+    };
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endraw"))?;
+
+    Ok((tokens, text))
+}
+
+fn parse_expr<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    let (tokens, _) = exact(source, tokens, Token::ExprStart)?;
+    let (tokens, span) = exact(source, tokens, Token::Other)?;
+    let (tokens, _) = exact(source, tokens, Token::ExprEnd)?;
+
+    let error = || {
+        Error::new(
+            span,
+            Some(Token::Other),
+            [Expected::Token(Token::ExprEnd)].into_iter().collect(),
+        )
+    };
+
+    let expr = source[span.range()].trim();
+    let (expr, format) = match expr.rfind("@{") {
+        Some(pos) => (expr[..pos].trim_end(), &expr[pos + 1..]),
+        None => (expr, "{}"),
+    };
+
+    // Filters
+    let mut pipes = top_level(expr)
+        .filter(|&(idx, c)| c == '|' && is_pipe(expr, idx))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    pipes.push(expr.len());
+    let mut filters = Vec::new();
+    for pipe in pipes.windows(2) {
+        let filter = expr[pipe[0] + 1..pipe[1]].trim();
+        let (name, args) = match filter.find('(') {
+            Some(pos) => (
+                filter[..pos].trim(),
+                untuple("(", filter[pos..].trim(), ")").ok_or_else(error)?,
+            ),
+            None => (filter, Vec::new()),
+        };
+        if name.is_empty() {
+            return Err(error());
+        }
+        filters.push(ast::Filter { name, args });
+    }
+    let expr = expr[..pipes[0]].trim();
+
+    Ok((
+        tokens,
+        ast::Item::Expr {
+            expr,
+            filters,
+            format,
+            ind: 0,
+        },
+    ))
+}
+
+fn parse_let<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    let (tokens, let_) = parse_block(source, tokens, BlockFilter::StartsWith("let"))?;
+
+    Ok((tokens, ast::Item::Let(let_)))
+}
+
+fn parse_use<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    let (tokens, use_) = parse_block(source, tokens, BlockFilter::StartsWith("use"))?;
+
+    Ok((tokens, ast::Item::Use(use_)))
+}
+
+fn parse_scope<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("scope"))?;
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endscope"))?;
+
+    Ok((tokens, ast::Item::Scope(body)))
+}
+
+fn parse_indent<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, indent) = parse_block(source, tokens, BlockFilter::StartsWith("indent"))?;
+    let indent = indent["indent".len()..].trim_start();
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endindent"))?;
+
+    Ok((tokens, ast::Item::Indent { indent, body }))
+}
+
+fn parse_capture<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, name) = parse_block(source, tokens, BlockFilter::StartsWith("capture"))?;
+    let name = name["capture".len()..].trim_start();
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endcapture"))?;
+
+    Ok((tokens, ast::Item::Capture { name, body }))
+}
+
+fn parse_for<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, for_) = parse_block(source, tokens, BlockFilter::Loop("for"))?;
+    let (label, for_) = split_label(for_);
+
+    // Body
+    let (mut tokens, body) = parse_ast(source, tokens)?;
+
+    // Else
+    let else_ = match parse_else(source, tokens) {
+        Ok((rest, else_)) => {
+            tokens = rest;
+            Some(else_)
+        }
+        Err(_) => None,
+    };
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endfor"))?;
+
+    Ok((
+        tokens,
+        ast::Item::For {
+            label,
+            for_,
+            pre: None,
+            body,
+            else_,
+        },
+    ))
+}
+
+fn parse_while<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, while_) = parse_block(source, tokens, BlockFilter::Loop("while"))?;
+    let (label, while_) = split_label(while_);
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endwhile"))?;
+
+    Ok((
+        tokens,
+        ast::Item::While {
+            label,
+            while_,
+            body,
+        },
+    ))
+}
+
+fn parse_break<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    let (tokens, break_) = parse_block(source, tokens, BlockFilter::StartsWith("break"))?;
+
+    Ok((tokens, ast::Item::Break(break_)))
+}
+
+fn parse_continue<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    let (tokens, continue_) = parse_block(source, tokens, BlockFilter::StartsWith("continue"))?;
+
+    Ok((tokens, ast::Item::Continue(continue_)))
+}
+
+fn parse_if<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, if_) = parse_block(source, tokens, BlockFilter::StartsWith("if"))?;
+
+    // Body
+    let (mut tokens, body) = parse_ast(source, tokens)?;
+
+    // Else ifs
+    let mut else_ifs = Vec::new();
+    while let Ok((rest, else_if)) = parse_else_if(source, tokens) {
+        tokens = rest;
+        else_ifs.push(else_if);
+    }
+
+    // Else
+    let else_ = match parse_else(source, tokens) {
+        Ok((rest, else_)) => {
+            tokens = rest;
+            Some(else_)
+        }
+        Err(_) => None,
+    };
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endif"))?;
+
+    Ok((
+        tokens,
+        ast::Item::If {
+            if_: (if_, body),
+            else_ifs,
+            else_,
+        },
+    ))
+}
+
+fn parse_else_if<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, (&'s str, ast::Ast<'s>)> {
+    // Start
+    let (tokens, else_if) = parse_block(source, tokens, BlockFilter::StartsWith("else if"))?;
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    Ok((tokens, (else_if, body)))
+}
+
+fn parse_else<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Ast<'s>> {
+    // Start
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("else"))?;
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    Ok((tokens, body))
+}
+
+fn parse_match<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (mut tokens, match_) = parse_block(source, tokens, BlockFilter::StartsWith("match"))?;
+
+    // Wheres
+    let mut wheres = Vec::new();
+    let mut run = true;
+    while run {
+        let (rest, ()) = skip_empty_other(source, tokens)?;
+        tokens = rest;
+
+        match parse_where(source, tokens) {
+            Ok((rest, where_)) => {
+                tokens = rest;
+                wheres.push(where_);
+            }
+            Err(_) => run = false,
+        }
+
+        let (rest, ()) = skip_empty_other(source, tokens)?;
+        tokens = rest;
+    }
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endmatch"))?;
+
+    Ok((tokens, ast::Item::Match { match_, wheres }))
+}
+
+fn parse_where<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, (&'s str, ast::Ast<'s>)> {
+    // Start
+    let (tokens, where_) = parse_block(source, tokens, BlockFilter::StartsWith("where"))?;
+    let arm = where_["where".len()..].trim_start();
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endwhere"))?;
+
+    Ok((tokens, (arm, body)))
+}
+
+fn parse_macro<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // TODO: Better error
+    let error_span = tokens.first().map(|s| s.span);
+    let error = move || {
+        Error::new(
+            error_span.unwrap(),
+            Some(Token::BlockStart),
+            [Expected::Block(BlockFilter::StartsWith("macro"))]
+                .into_iter()
+                .collect(),
+        )
+    };
+
+    // Start
+    let (tokens, macro_) = parse_block(source, tokens, BlockFilter::StartsWith("macro"))?;
+
+    let macro_ = macro_["macro".len()..].trim();
+    let (name, params) = macro_.split_at(macro_.find('|').ok_or_else(error)?);
+    let name = name.trim();
+    let params = untuple("|", params.trim(), "|").ok_or_else(error)?;
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endmacro"))?;
+
+    Ok((tokens, ast::Item::Macro { name, params, body }))
+}
+
+fn parse_call<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    // TODO: Better error
+    let error_span = tokens.first().map(|s| s.span);
+    let error = move || {
+        Error::new(
+            error_span.unwrap(),
+            Some(Token::BlockStart),
+            [Expected::Block(BlockFilter::StartsWith("call"))]
+                .into_iter()
+                .collect(),
+        )
+    };
+
+    let (tokens, call) = parse_block(source, tokens, BlockFilter::StartsWith("call"))?;
+
+    let call = call["call".len()..].trim();
+    let (name, args) = call.split_at(call.find('(').ok_or_else(error)?);
+    let name = name.trim();
+    let args = untuple("(", args.trim(), ")").ok_or_else(error)?;
+
+    // The caller is parsed by `parse_items`, which knows the calls around this one
+    Ok((
+        tokens,
+        ast::Item::Call {
+            name,
+            args,
+            caller: None,
+            ind: 0,
+        },
+    ))
+}
+
+/// Counts the `endcall` tags in `tokens` up to the end of the current block, without those of
+/// nested blocks.
+fn count_endcalls(source: &str, tokens: &[Spanned<Token>]) -> usize {
+    const BLOCKS: &[&str] = &[
+        "scope", "indent", "capture", "for", "while", "if", "match", "where", "macro", "block",
+        "raw",
+    ];
+
+    let mut depth = 0;
+    let mut count = 0;
+    for pair in tokens.windows(2) {
+        if pair[0].node != Token::BlockStart || pair[1].node != Token::Other {
+            continue;
+        }
+        let content = split_label(source[pair[1].span.range()].trim()).1;
+        let keyword = content
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap_or("");
+        match keyword {
+            "endcall" if depth == 0 => count += 1,
+            "endcall" => (),
+            "else" if depth == 0 => break,
+            keyword if BLOCKS.contains(&keyword) => depth += 1,
+            keyword if keyword.starts_with("end") => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            }
+            _ => (),
+        }
+    }
+    count
+}
+
+fn parse_caller<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    let (tokens, caller) = parse_block(source, tokens, BlockFilter::Equals("caller"))?;
+
+    Ok((tokens, ast::Item::Caller { caller, ind: 0 }))
+}
+
+fn parse_include<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    // TODO: Better error
+    let error_span = tokens.first().map(|s| s.span);
+    let error = move || {
+        Error::new(
+            error_span.unwrap(),
+            Some(Token::BlockStart),
+            [Expected::Block(BlockFilter::StartsWith("include"))]
+                .into_iter()
+                .collect(),
+        )
+    };
+
+    let (tokens, include) = parse_block(source, tokens, BlockFilter::StartsWith("include"))?;
+
+    let path = unquote(include["include".len()..].trim()).ok_or_else(error)?;
+
+    Ok((tokens, ast::Item::Include(path)))
+}
+
+fn parse_import<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    // TODO: Better error
+    let error_span = tokens.first().map(|s| s.span);
+    let error = move || {
+        Error::new(
+            error_span.unwrap(),
+            Some(Token::BlockStart),
+            [Expected::Block(BlockFilter::StartsWith("import"))]
+                .into_iter()
+                .collect(),
+        )
+    };
+
+    let (tokens, import) = parse_block(source, tokens, BlockFilter::StartsWith("import"))?;
+
+    let import = import["import".len()..].trim();
+    let (path, namespace) = match unquote(import) {
+        Some(path) => (path, None),
+        None => {
+            let (path, namespace) = import.rsplit_once(" as ").ok_or_else(error)?;
+            (
+                unquote(path.trim()).ok_or_else(error)?,
+                Some(namespace.trim()),
+            )
+        }
+    };
+
+    Ok((tokens, ast::Item::Import { path, namespace }))
+}
+
+fn parse_extends<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    // TODO: Better error
+    let error_span = tokens.first().map(|s| s.span);
+    let error = move || {
+        Error::new(
+            error_span.unwrap(),
+            Some(Token::BlockStart),
+            [Expected::Block(BlockFilter::StartsWith("extends"))]
+                .into_iter()
+                .collect(),
+        )
+    };
+
+    let (tokens, extends) = parse_block(source, tokens, BlockFilter::StartsWith("extends"))?;
+
+    let path = unquote(extends["extends".len()..].trim()).ok_or_else(error)?;
+
+    Ok((tokens, ast::Item::Extends(path)))
+}
+
+fn parse_named_block<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+) -> Result<'t, ast::Item<'s>> {
+    // Start
+    let (tokens, block) = parse_block(source, tokens, BlockFilter::StartsWith("block"))?;
+    let name = block["block".len()..].trim();
+
+    // Body
+    let (tokens, body) = parse_ast(source, tokens)?;
+
+    // End
+    let (tokens, _) = parse_block(source, tokens, BlockFilter::Equals("endblock"))?;
+
+    Ok((tokens, ast::Item::Block { name, body }))
+}
+
+fn parse_super<'s, 't>(source: &'s str, tokens: &'t [Spanned<Token>]) -> Result<'t, ast::Item<'s>> {
+    let (tokens, super_) = parse_block(source, tokens, BlockFilter::Equals("super"))?;
+
+    Ok((tokens, ast::Item::Super(super_)))
+}
+
+fn parse_block<'s, 't>(
+    source: &'s str,
+    tokens: &'t [Spanned<Token>],
+    filter: BlockFilter,
+) -> Result<'t, &'s str> {
+    let error = || [Expected::Block(filter)].into_iter().collect();
+
+    let (tokens, _) = exact(source, tokens, Token::BlockStart).map_expected(|_| error())?;
+    let (tokens, span) = exact(source, tokens, Token::Other).map_expected(|_| error())?;
+    let (tokens, _) = exact(source, tokens, Token::BlockEnd).map_expected(|_| error())?;
+
+    let content = source[span.range()].trim();
+
+    match filter {
+        BlockFilter::Equals(f) => {
+            if content != f {
+                return Err(Error::new(span, Some(Token::BlockStart), error()));
+            }
+        }
+        BlockFilter::StartsWith(f) => {
+            if !content.starts_with(f) {
+                return Err(Error::new(span, Some(Token::BlockStart), error()));
+            }
+        }
+        BlockFilter::Loop(f) => {
+            if !split_label(content).1.starts_with(f) {
+                return Err(Error::new(span, Some(Token::BlockStart), error()));
+            }
+        }
+    }
+
+    Ok((tokens, source[span.range()].trim()))
+}
+
+/// Splits the label `'label:` off a loop.
+fn split_label(content: &str) -> (Option<&str>, &str) {
+    if let Some(label) = content.strip_prefix('\'') {
+        let len = label
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(label.len());
+        if len > 0 {
+            if let Some(rest) = label[len..].trim_start().strip_prefix(':') {
+                return (Some(&content[..len + 1]), rest.trim_start());
+            }
+        }
+    }
+    (None, content)
+}
+
+fn skip_empty_other<'t>(source: &str, tokens: &'t [Spanned<Token>]) -> Result<'t, ()> {
+    match exact(source, tokens, Token::Other) {
+        Ok((tokens, span)) if source[span.range()].trim().is_empty() => return Ok((tokens, ())),
+        _ => (),
+    }
+
+    Ok((tokens, ()))
+}
+
+fn exact<'t>(source: &str, tokens: &'t [Spanned<Token>], token: Token) -> Result<'t, Span> {
+    match tokens.first() {
+        Some(t) if t.node == token => Ok((&tokens[1..], t.span)),
+        Some(t) => Err(Error::new(
+            t.span,
+            Some(t.node),
+            [Expected::Token(token)].into_iter().collect(),
+        )),
+        None => Err(Error::new(
+            (source.len()..source.len()).into(),
+            None,
+            [Expected::Token(token)].into_iter().collect(),
+        )),
+    }
+}
+
+trait ResultExt {
+    fn alt<F: Fn() -> Self>(self, f: F) -> Self;
+
+    fn map_expected<F: Fn(HashSet<Expected>) -> HashSet<Expected>>(self, f: F) -> Self;
+}
+
+impl<T> ResultExt for Result<'_, T> {
+    fn alt<F: Fn() -> Self>(self, f: F) -> Self {
+        let err = match self {
+            Ok(ok) => return Ok(ok),
+            Err(err) => err,
+        };
+
+        let alt_err = match f() {
+            Ok(ok) => return Ok(ok),
+            Err(alt_err) => alt_err,
+        };
+
+        match err.span.cmp(&alt_err.span) {
+            Ordering::Greater => Err(err),
+            Ordering::Less => Err(alt_err),
+            Ordering::Equal => Err(Error::new(
+                err.span,
+                err.token,
+                err.expected.into_iter().chain(alt_err.expected).collect(),
+            )),
+        }
+    }
+
+    fn map_expected<F: Fn(HashSet<Expected>) -> HashSet<Expected>>(self, f: F) -> Self {
+        match self {
+            Ok(ok) => Ok(ok),
+            Err(mut err) => {
+                err.expected = f(err.expected);
+                Err(err)
+            }
+        }
+    }
+}
+
+fn untuple<'s>(start: &str, t: &'s str, end: &str) -> Option<Vec<&'s str>> {
+    let t = t.strip_prefix(start)?;
+    let t = t.strip_suffix(end)?;
+
+    let mut items = Vec::new();
+    let mut pos = 0;
+    for (c_idx, c) in top_level(t) {
+        if c == ',' {
+            items.push(t[pos..c_idx].trim());
+            pos = c_idx + 1;
+        }
+    }
+
+    let rest = t[pos..].trim();
+    if !rest.is_empty() {
+        items.push(rest);
+    }
+
+    Some(items)
+}
+
+/// Returns all chars of `s` with their position, that are neither enclosed in brackets nor part of
+/// a string or char literal.
+fn top_level(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut chars = s.char_indices().peekable();
+    let mut depth = 0;
+
+    std::iter::from_fn(move || loop {
+        let (c_idx, c) = chars.next()?;
+        match c {
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' => depth -= 1,
+            '"' => {
+                let prefix = s[..c_idx].trim_end_matches('#');
+                if prefix.ends_with('r') {
+                    // Raw string literal
+                    let end = format!("\"{}", &s[prefix.len()..c_idx]);
+                    let end = s[c_idx + 1..]
+                        .find(&end)
+                        .map_or(s.len(), |pos| c_idx + 1 + pos + end.len());
+                    while chars.next_if(|&(idx, _)| idx < end).is_some() {}
+                } else {
+                    // String literal
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '\\' => {
+                                chars.next();
+                            }
+                            '"' => break,
+                            _ => (),
+                        }
+                    }
+                }
+            }
+            '\'' => {
+                // Char literal, otherwise lifetime
+                let mut lookahead = chars.clone();
+                let is_char = matches!(
+                    (lookahead.next(), lookahead.next()),
+                    (Some((_, '\\')), _) | (Some(_), Some((_, '\'')))
+                );
+                if is_char {
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '\\' => {
+                                chars.next();
+                            }
+                            '\'' => break,
+                            _ => (),
+                        }
+                    }
+                }
+            }
+            _ if depth == 0 => return Some((c_idx, c)),
+            _ => (),
+        }
+    })
+}
+
+/// Returns true if the `|` at `idx` is a filter pipe, i.e. it is not part of `||` and is followed
+/// by the name of a filter. Any other `|`, e.g. in `6 | 3` or `x |= 1`, belongs to the expression.
+fn is_pipe(s: &str, idx: usize) -> bool {
+    !s[..idx].ends_with('|')
+        && s[idx + 1..]
+            .trim_start()
+            .starts_with(|c: char| c.is_alphabetic() || c == '_')
+}
+
+fn unquote(s: &str) -> Option<&str> {
+    s.strip_prefix('"')?.strip_suffix('"')
+}
+
+fn indent(s: &str) -> Option<usize> {
+    let mut indent = None;
+
+    let lines = s.lines().collect::<Vec<_>>();
+    for (idx, line) in lines.iter().enumerate() {
+        let is_first = idx == 0;
+        let is_last = idx == lines.len() - 1;
+
+        if line.is_empty() || is_first || (is_last && line.chars().all(|c| c == ' ')) {
+            continue;
+        }
+
+        let line_ind = line.len() - line.trim_start_matches(' ').len();
+        match &mut indent {
+            Some(indent) => {
+                if line_ind < *indent {
+                    *indent = line_ind;
+                }
+            }
+            None => indent = Some(line_ind),
+        }
+    }
+
+    indent
+}
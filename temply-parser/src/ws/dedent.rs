@@ -23,13 +23,14 @@ fn dedent_items(items: &mut [ast::Item<'_>], mut base_indent: usize) {
                 }
             }
             _ => {
-                // Special case macro/call
+                // Special case macro/call/indent/capture, the bodies of macros and callers are
+                // indented when they are called, the body of an indent block by its prefix and
+                // the body of a capture block where it is written
                 let base_indent = match item {
-                    ast::Item::Macro { .. } => 0,
-                    ast::Item::Call { ind, .. } => {
-                        *ind = base_indent;
-                        base_indent
-                    }
+                    ast::Item::Macro { .. }
+                    | ast::Item::Call { .. }
+                    | ast::Item::Indent { .. }
+                    | ast::Item::Capture { .. } => 0,
                     _ => base_indent,
                 };
 
@@ -65,14 +66,14 @@ fn dedent_ast(ast: &mut ast::Ast<'_>, dedent: usize) {
             ast::Item::Text(text) => {
                 for line in &mut text.lines {
                     if is_at_line_start && !line.content.is_empty() {
-                        line.content = &line.content[dedent..];
+                        line.content = strip_indent(line.content, dedent);
                     }
                     is_at_line_start = true;
                 }
 
                 if is_at_line_start && !text.trailing.is_empty() {
                     if !(is_last && text.trailing.chars().all(|c| c == ' ')) {
-                        text.trailing = &text.trailing[dedent..];
+                        text.trailing = strip_indent(text.trailing, dedent);
                     }
                     is_at_line_start = false;
                 }
@@ -86,3 +87,9 @@ fn dedent_ast(ast: &mut ast::Ast<'_>, dedent: usize) {
         }
     }
 }
+
+/// Removes up to `dedent` leading spaces.
+fn strip_indent(s: &str, dedent: usize) -> &str {
+    let indent = s.len() - s.trim_start_matches(' ').len();
+    &s[indent.min(dedent)..]
+}
@@ -0,0 +1,136 @@
+use crate::parser::ast;
+
+/// Computes the indentation of expressions, calls and callers, which are only preceded by spaces
+/// on their line. Their output is indented by it after every line break. Must run after trimming,
+/// so that the text matches the output.
+pub fn indent(ast: &mut ast::Ast<'_>) {
+    indent_items(&mut ast.items, Column::LineStart(0));
+}
+
+/// The column of the output, as far as it is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    /// Only spaces have been written on the current line
+    LineStart(usize),
+    /// Something else has been written on the current line
+    Other,
+}
+
+impl Column {
+    fn ind(self) -> usize {
+        match self {
+            Column::LineStart(ind) => ind,
+            Column::Other => 0,
+        }
+    }
+
+    fn merge(self, other: Column) -> Column {
+        if self == other {
+            self
+        } else {
+            Column::Other
+        }
+    }
+}
+
+fn indent_items(items: &mut [ast::Item<'_>], mut column: Column) -> Column {
+    for item in items {
+        column = match item {
+            ast::Item::Text(text) => text_column(text, column),
+            ast::Item::Expr { ind, .. } | ast::Item::Caller { ind, .. } => {
+                *ind = column.ind();
+                Column::Other
+            }
+            ast::Item::Call { caller, ind, .. } => {
+                *ind = column.ind();
+                // The caller is indented by the call inside of the macro
+                if let Some(caller) = caller {
+                    indent_items(&mut caller.items, Column::LineStart(0));
+                }
+                Column::Other
+            }
+            // The body of a macro is indented by its calls, the body of a capture block where it is
+            // written
+            ast::Item::Macro { body, .. } | ast::Item::Capture { body, .. } => {
+                indent_items(&mut body.items, Column::LineStart(0));
+                column
+            }
+            // The body of an indent block is indented by its prefix, which starts every line
+            ast::Item::Indent { body, .. } => {
+                indent_items(&mut body.items, Column::LineStart(0));
+                Column::Other
+            }
+            // Every branch starts at the column before the block, the column after the block is
+            // known if it is the same after every branch
+            ast::Item::For {
+                pre, body, else_, ..
+            } => {
+                let after = indent_loop(&mut body.items, pre.as_ref(), column);
+                match else_ {
+                    Some(else_) => after.merge(indent_items(&mut else_.items, column)),
+                    None => after,
+                }
+            }
+            ast::Item::While { body, .. } => indent_loop(&mut body.items, None, column),
+            ast::Item::If {
+                if_,
+                else_ifs,
+                else_,
+            } => {
+                let mut after = indent_items(&mut if_.1.items, column);
+                for (_, body) in else_ifs {
+                    after = after.merge(indent_items(&mut body.items, column));
+                }
+                match else_ {
+                    Some(else_) => after.merge(indent_items(&mut else_.items, column)),
+                    None => after.merge(column),
+                }
+            }
+            ast::Item::Match { wheres, .. } => {
+                wheres.iter_mut().fold(column, |after, (_, body)| {
+                    after.merge(indent_items(&mut body.items, column))
+                })
+            }
+            ast::Item::Scope(body) | ast::Item::Block { body, .. } => {
+                indent_items(&mut body.items, column)
+            }
+            ast::Item::Comment(_)
+            | ast::Item::Let(_)
+            | ast::Item::Use(_)
+            | ast::Item::Break(_)
+            | ast::Item::Continue(_)
+            | ast::Item::Import { .. }
+            | ast::Item::Extends(_)
+            | ast::Item::Super(_) => column,
+            // The output of an include is unknown
+            ast::Item::Include(_) => Column::Other,
+        };
+    }
+    column
+}
+
+/// Later iterations of a loop start at the column after the previous one, so the body is indented
+/// again if that differs from the column before the loop.
+fn indent_loop(items: &mut [ast::Item<'_>], pre: Option<&ast::Text<'_>>, column: Column) -> Column {
+    let next = |after| pre.map_or(after, |pre| text_column(pre, after));
+    let after = indent_items(items, column);
+    let start = column.merge(next(after));
+    if start == column {
+        return column.merge(after);
+    }
+    column.merge(indent_items(items, start))
+}
+
+fn text_column(text: &ast::Text<'_>, column: Column) -> Column {
+    let column = if text.lines.is_empty() {
+        column
+    } else {
+        Column::LineStart(0)
+    };
+    match column {
+        Column::LineStart(ind) if text.trailing.bytes().all(|b| b == b' ') => {
+            Column::LineStart(ind + text.trailing.len())
+        }
+        _ => Column::Other,
+    }
+}
@@ -0,0 +1,6 @@
+mod dedent;
+mod indent;
+mod trim;
+mod util;
+
+pub use self::{dedent::dedent, indent::indent, trim::trim, util::inner_asts_mut};
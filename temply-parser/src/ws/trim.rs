@@ -10,6 +10,7 @@ fn trim_items(items: &mut [ast::Item<'_>]) {
     for item in items {
         match item {
             ast::Item::For {
+                label: _,
                 for_: _,
                 pre,
                 body,
@@ -60,7 +61,7 @@ fn text_trim_start<'s>(text: &mut ast::Text<'s>) -> Option<ast::Text<'s>> {
         match text.lines.first_mut() {
             Some(line) => {
                 let trimmed = line.content.trim_start();
-                if trimmed.len() == 0 {
+                if trimmed.is_empty() {
                     pre.lines.push(text.lines.remove(0));
                 } else if trimmed.len() < line.content.len() {
                     pre.trailing = &line.content[0..line.content.len() - trimmed.len()];
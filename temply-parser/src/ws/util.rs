@@ -8,12 +8,25 @@ pub fn inner_asts_mut<'a, 's>(
     match item {
         ast::Item::Text(_) => (),
         ast::Item::Comment(_) => (),
-        ast::Item::Expr(_, _) => (),
+        ast::Item::Expr {
+            expr: _,
+            filters: _,
+            format: _,
+            ind: _,
+        } => (),
         ast::Item::Let(_) => (),
+        ast::Item::Use(_) => (),
         ast::Item::Scope(body) => {
             asts.push(body);
         }
+        ast::Item::Indent { indent: _, body } => {
+            asts.push(body);
+        }
+        ast::Item::Capture { name: _, body } => {
+            asts.push(body);
+        }
         ast::Item::For {
+            label: _,
             for_: _,
             pre: _,
             body,
@@ -24,6 +37,15 @@ pub fn inner_asts_mut<'a, 's>(
                 asts.push(else_);
             }
         }
+        ast::Item::While {
+            label: _,
+            while_: _,
+            body,
+        } => {
+            asts.push(body);
+        }
+        ast::Item::Break(_) => (),
+        ast::Item::Continue(_) => (),
         ast::Item::If {
             if_,
             else_ifs,
@@ -52,8 +74,24 @@ pub fn inner_asts_mut<'a, 's>(
         ast::Item::Call {
             name: _,
             args: _,
+            caller,
             ind: _,
+        } => {
+            if let Some(caller) = caller {
+                asts.push(caller);
+            }
+        }
+        ast::Item::Caller { caller: _, ind: _ } => (),
+        ast::Item::Include(_) => (),
+        ast::Item::Import {
+            path: _,
+            namespace: _,
         } => (),
+        ast::Item::Extends(_) => (),
+        ast::Item::Block { name: _, body } => {
+            asts.push(body);
+        }
+        ast::Item::Super(_) => (),
     }
 
     asts.into_iter()
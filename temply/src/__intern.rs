@@ -1,18 +1,117 @@
 pub mod indent {
+    use std::borrow::Cow;
+    use std::cell::Cell;
     use std::fmt::{self};
 
+    /// The prefix of an indent block, either a number of spaces or a string.
+    pub trait Prefix {
+        fn prefix(&self) -> Cow<'_, str>;
+    }
+
+    impl Prefix for usize {
+        fn prefix(&self) -> Cow<'_, str> {
+            Cow::Owned(" ".repeat(*self))
+        }
+    }
+
+    impl Prefix for char {
+        fn prefix(&self) -> Cow<'_, str> {
+            Cow::Owned(self.to_string())
+        }
+    }
+
+    impl Prefix for str {
+        fn prefix(&self) -> Cow<'_, str> {
+            Cow::Borrowed(self)
+        }
+    }
+
+    impl Prefix for String {
+        fn prefix(&self) -> Cow<'_, str> {
+            Cow::Borrowed(self)
+        }
+    }
+
+    impl<T: Prefix + ?Sized> Prefix for &T {
+        fn prefix(&self) -> Cow<'_, str> {
+            (**self).prefix()
+        }
+    }
+
+    thread_local! {
+        /// The number of expressions being written
+        static SITE: Cell<usize> = Cell::new(0);
+        /// The expression whose indentation is disabled, or zero
+        static RAW: Cell<usize> = Cell::new(0);
+    }
+
+    /// Marks an expression as being written, from its creation until it is dropped.
+    pub struct Site(usize);
+
+    impl Site {
+        pub fn enter() -> Self {
+            SITE.with(|site| {
+                site.set(site.get() + 1);
+                Site(site.get())
+            })
+        }
+    }
+
+    impl Drop for Site {
+        fn drop(&mut self) {
+            SITE.with(|site| site.set(site.get() - 1));
+        }
+    }
+
+    /// Runs `f` while the indentation of the innermost expression being written is disabled.
+    /// Calls, indent blocks and expressions written inside of `f` still indent.
+    pub fn raw<R>(f: impl FnOnce() -> R) -> R {
+        struct Guard(usize);
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                RAW.with(|raw| raw.set(self.0));
+            }
+        }
+
+        let site = SITE.with(|site| site.get());
+        let _guard = Guard(RAW.with(|raw| raw.replace(site)));
+        f()
+    }
+
     pub struct Indenter<'a, T: ?Sized> {
         inner: &'a mut T,
-        indentation: &'static str,
+        indentation: &'a str,
         needs_indent: bool,
+        /// The expression this writer indents, or zero
+        site: usize,
     }
 
     impl<'a, T: ?Sized> Indenter<'a, T> {
-        pub fn new(f: &'a mut T, indentation: &'static str) -> Self {
+        /// Creates a writer for a call, which indents all lines after the first.
+        pub fn new(f: &'a mut T, indentation: &'a str) -> Self {
             Self {
                 inner: f,
                 indentation,
                 needs_indent: false,
+                site: 0,
+            }
+        }
+
+        /// Creates a writer for the expression `site`, whose indentation can be disabled with
+        /// [`raw`].
+        pub fn expr(f: &'a mut T, indentation: &'a str, site: &Site) -> Self {
+            Self {
+                site: site.0,
+                ..Self::new(f, indentation)
+            }
+        }
+
+        /// Creates a writer for an indent block, which also prefixes the first line.
+        pub fn prefixed(f: &'a mut T, prefix: &'a str) -> Self {
+            Self {
+                needs_indent: true,
+                ..Self::new(f, prefix)
             }
         }
     }
@@ -22,8 +121,16 @@ pub mod indent {
         T: fmt::Write + ?Sized,
     {
         fn write_str(&mut self, s: &str) -> fmt::Result {
+            if self.site != 0 && RAW.with(|raw| raw.get()) == self.site {
+                return self.inner.write_str(s);
+            }
+
             for (idx, line) in s.split('\n').enumerate() {
                 if idx > 0 {
+                    // Empty lines only get the prefix without trailing whitespace
+                    if self.needs_indent {
+                        self.inner.write_str(self.indentation.trim_end())?;
+                    }
                     self.inner.write_char('\n')?;
                     self.needs_indent = true;
                 }
@@ -45,3 +152,186 @@ pub mod indent {
         }
     }
 }
+
+pub mod escape {
+    use std::cell::Cell;
+    use std::fmt::{self};
+
+    thread_local! {
+        static RAW: Cell<usize> = Cell::new(0);
+    }
+
+    /// Runs `f` while escaping is disabled for the current thread. Only writers created outside of
+    /// `f` are affected, so that writers created inside of `f` still escape.
+    pub fn raw<R>(f: impl FnOnce() -> R) -> R {
+        struct Guard;
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                RAW.with(|raw| raw.set(raw.get() - 1));
+            }
+        }
+
+        RAW.with(|raw| raw.set(raw.get() + 1));
+        let _guard = Guard;
+        f()
+    }
+
+    fn level() -> usize {
+        RAW.with(|raw| raw.get())
+    }
+
+    pub struct Html<'a, T: ?Sized> {
+        inner: &'a mut T,
+        level: usize,
+    }
+
+    impl<'a, T: ?Sized> Html<'a, T> {
+        pub fn new(f: &'a mut T) -> Self {
+            Self {
+                inner: f,
+                level: level(),
+            }
+        }
+    }
+
+    impl<T> fmt::Write for Html<'_, T>
+    where
+        T: fmt::Write + ?Sized,
+    {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            if level() > self.level {
+                return self.inner.write_str(s);
+            }
+
+            let mut pos = 0;
+            for (idx, b) in s.bytes().enumerate() {
+                let escaped = match b {
+                    b'&' => "&amp;",
+                    b'<' => "&lt;",
+                    b'>' => "&gt;",
+                    b'"' => "&quot;",
+                    b'\'' => "&#x27;",
+                    _ => continue,
+                };
+                self.inner.write_str(&s[pos..idx])?;
+                self.inner.write_str(escaped)?;
+                pos = idx + 1;
+            }
+
+            self.inner.write_str(&s[pos..])
+        }
+    }
+}
+
+pub mod loop_ {
+    use std::ops::Deref;
+
+    /// The `loop` variable of a for loop.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Loop {
+        pub index: usize,
+        pub index0: usize,
+        pub first: bool,
+        pub last: bool,
+    }
+
+    impl Loop {
+        pub fn new(index0: usize, last: bool) -> Self {
+            Self {
+                index: index0 + 1,
+                index0,
+                first: index0 == 0,
+                last,
+            }
+        }
+    }
+
+    /// The `loop` variable of a for loop over an `ExactSizeIterator`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SizedLoop {
+        pub length: usize,
+        pub revindex: usize,
+        pub revindex0: usize,
+        inner: Loop,
+    }
+
+    impl SizedLoop {
+        pub fn new(inner: Loop, length: usize) -> Self {
+            Self {
+                length,
+                revindex: length - inner.index0,
+                revindex0: length - inner.index,
+                inner,
+            }
+        }
+    }
+
+    impl Deref for SizedLoop {
+        type Target = Loop;
+
+        fn deref(&self) -> &Self::Target {
+            &self.inner
+        }
+    }
+}
+
+pub mod render {
+    use std::fmt::{self};
+
+    /// A template given by the closure of `render!` or `write!`. It renders the same output every
+    /// time it is displayed, e.g. if a writer formats its arguments twice.
+    pub struct Render<F>(F);
+
+    impl<F> Render<F>
+    where
+        F: Fn(&mut dyn fmt::Write) -> fmt::Result,
+    {
+        pub fn new(f: F) -> Self {
+            Self(f)
+        }
+    }
+
+    impl<F> fmt::Display for Render<F>
+    where
+        F: Fn(&mut dyn fmt::Write) -> fmt::Result,
+    {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            (self.0)(f)
+        }
+    }
+}
+
+pub mod io {
+    use crate::IoWriter;
+    use std::fmt;
+    use std::io::{self, Write};
+
+    /// Renders into a new string with the capacity `size_hint`, see
+    /// [`Template::render_to_string`](crate::Template::render_to_string).
+    pub fn render_to_string(
+        size_hint: usize,
+        render: impl FnOnce(&mut String) -> fmt::Result,
+    ) -> String {
+        let mut buffer = String::with_capacity(size_hint);
+        render(&mut buffer).expect("a Display implementation returned an error unexpectedly");
+        buffer
+    }
+
+    /// Renders into `writer` and flushes it, see [`Template::write_to`](crate::Template::write_to).
+    pub fn write_to<W: Write>(
+        writer: W,
+        render: impl FnOnce(&mut IoWriter<W>) -> fmt::Result,
+    ) -> io::Result<()> {
+        let mut writer = IoWriter::new(writer);
+        match render(&mut writer) {
+            Ok(()) => writer.into_inner().flush(),
+            Err(fmt::Error) => Err(writer.take_error().unwrap_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    "a Display implementation returned an error",
+                )
+            })),
+        }
+    }
+}
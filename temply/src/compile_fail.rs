@@ -67,3 +67,353 @@
 //! "#]
 //! struct MyTemplate;
 //! ```
+//!
+//! # Include cycle
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template = "../tests/templates/include_cycle_a.template"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Extends cycle
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template = "../tests/templates/extends_self.template"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Extends cycle of an inline template
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = r#"{% extends "../tests/templates/extends_cycle_a.template" %}"#]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Unknown block in an extending template
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = r#"{% extends "../tests/templates/base.template" %}{% block bdoy %}{% endblock %}"#]
+//! struct MyTemplate {
+//!     year: u32,
+//! }
+//! ```
+//!
+//! # Missing template file
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template = "missing.template"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Missing template source
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Unknown template option
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template(unknown = "value")]
+//! #[template_inline = ""]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Invalid filter name
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{{ 12 | 1filter }}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Parse error after non-ASCII text
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "Grüße {% endfor %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Missing template of variant
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! enum MyTemplate {
+//!     #[template_inline = "A"]
+//!     A,
+//!     B,
+//! }
+//! ```
+//!
+//! # Options of a variant without a template
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "Other"]
+//! enum MyTemplate {
+//!     #[template_inline = "A"]
+//!     A,
+//!     #[template(escape = "html")]
+//!     B,
+//! }
+//! ```
+//!
+//! # Display on a variant
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! enum MyTemplate {
+//!     #[template_inline = "A"]
+//!     #[template(display)]
+//!     A,
+//! }
+//! ```
+//!
+//! # Missing default of named templates
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template(name = "a", inline = "A")]
+//! #[template(name = "b", inline = "B")]
+//! struct MyTemplate;
+//! ```
+//!
+//! # More than one default template
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = ""]
+//! #[template(name = "a", inline = "A", default)]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Break outside of a loop
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% break %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Unclosed while block
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% while true %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Caller outside of a macro
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% caller %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Import of a template with content outside of macros
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% import \"../tests/templates/import_text.template\" %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Invalid import namespace
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% import \"../tests/templates/macros.template\" as a-b %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Template function with a body
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[temply::template(inline = "{{ x }}")]
+//! fn x(x: u32) -> impl Template {
+//!     todo!()
+//! }
+//! ```
+//!
+//! # Template function without a template source
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[temply::template(escape = "html")]
+//! fn x(x: u32) -> impl Template {}
+//! ```
+//!
+//! # Template function with a pattern parameter
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[temply::template(inline = "{{ a }}")]
+//! fn x((a, b): (u32, u32)) -> impl Template {}
+//! ```
+//!
+//! # Write without a destination
+//!
+//! ```compile_fail
+//! let _ = temply::write!("{{ x }}", x = 1);
+//! ```
+//!
+//! # Unclosed indent block
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% indent 4 %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Invalid indent prefix
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% indent true %}{% endindent %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Unclosed capture block
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% capture x %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Capture without a name
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% capture %}{% endcapture %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Display on a named template
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template(name = "short", inline = "", display)]
+//! #[template_inline = ""]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Endcall without a call
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% macro m || %}{% caller %}{% endmacro %}{% call m() %}x{% endcall %}{% endcall %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Endcall closing a call outside of its block
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% macro m || %}{% caller %}{% endmacro %}{% call m() %}{% if true %}{% endcall %}{% endif %}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Moving a variable out of render
+//!
+//! ```compile_fail
+//! let v = vec![String::from("a")];
+//! let rendered = temply::render!("{% for s in v %}{{ s }}{% endfor %}");
+//! ```
+//!
+//! # Unclosed raw block
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template_inline = "{% raw %}{{ x }}"]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Ambiguous delimiters
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template(syntax(expr = ("<<", ">>"), block = ("<<", "%}")))]
+//! #[template_inline = ""]
+//! struct MyTemplate;
+//! ```
+//!
+//! # Empty delimiter
+//!
+//! ```compile_fail
+//! use temply::Template;
+//!
+//! #[derive(Debug, Template)]
+//! #[template(syntax(comment = ("", "#}")))]
+//! #[template_inline = ""]
+//! struct MyTemplate;
+//! ```
@@ -0,0 +1,594 @@
+use super::{Error, Value, MAX_SIZE, MAX_WIDTH};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// An expression of a dynamic template.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Literal(Value),
+    List(Vec<Expr>),
+    Var(String),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+/// A pattern of a let, for or macro parameter.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Ident(String),
+    Wildcard,
+    Tuple(Vec<Pattern>),
+}
+
+/// The variables an expression is evaluated against.
+pub trait Env {
+    fn lookup(&self, name: &str) -> Option<&Value>;
+
+    fn root(&self) -> &Value;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    Ident(String),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Punct(&'static str),
+}
+
+const PUNCTS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "::", "<", ">", "!", "-", "&", "*", ".", "[", "]", "(",
+    ")", ",", ":", "=",
+];
+
+/// Splits a piece of code into tokens.
+pub fn tokenize(code: &str) -> Result<Vec<Tok>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = code.char_indices().peekable();
+
+    while let Some(&(pos, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_alphabetic() || c == '_' {
+            let end = code[pos..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .map_or(code.len(), |len| pos + len);
+            tokens.push(Tok::Ident(code[pos..end].to_string()));
+            while chars.peek().map_or(false, |&(idx, _)| idx < end) {
+                chars.next();
+            }
+        } else if c.is_ascii_digit() {
+            let (token, len) = number(&code[pos..])?;
+            tokens.push(token);
+            while chars.peek().map_or(false, |&(idx, _)| idx < pos + len) {
+                chars.next();
+            }
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, end)) if end == c => break,
+                    Some((_, '\\')) => value.push(match chars.next() {
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, '0')) => '\0',
+                        Some((_, c @ ('\\' | '"' | '\''))) => c,
+                        _ => return Err(Error::new("unsupported escape sequence")),
+                    }),
+                    Some((_, c)) => value.push(c),
+                    None => return Err(Error::new("unterminated string literal")),
+                }
+            }
+            tokens.push(Tok::Str(value));
+        } else {
+            let punct = PUNCTS
+                .iter()
+                .find(|punct| code[pos..].starts_with(*punct))
+                .ok_or_else(|| Error::new(format!("unexpected `{}`", c)))?;
+            tokens.push(Tok::Punct(punct));
+            for _ in 0..punct.len() {
+                chars.next();
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Lexes a number literal at the start of `code`, optionally followed by a type suffix.
+fn number(code: &str) -> Result<(Tok, usize), Error> {
+    let mut len = code
+        .find(|c: char| !c.is_ascii_digit() && c != '_')
+        .unwrap_or(code.len());
+    let mut float = false;
+    if code[len..].starts_with('.') && code[len + 1..].starts_with(|c: char| c.is_ascii_digit()) {
+        float = true;
+        len += 1 + code[len + 1..]
+            .find(|c: char| !c.is_ascii_digit() && c != '_')
+            .unwrap_or(code.len() - len - 1);
+    }
+    let digits = code[..len].replace('_', "");
+
+    let suffix_len = code[len..]
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(code.len() - len);
+    let suffix = &code[len..len + suffix_len];
+    let float = match suffix {
+        "" => float,
+        "f32" | "f64" => true,
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize"
+            if !float =>
+        {
+            false
+        }
+        _ => return Err(Error::new(format!("invalid number `{}{}`", digits, suffix))),
+    };
+
+    let token = if float {
+        Tok::Float(digits.parse().map_err(|_| invalid_number(&digits))?)
+    } else {
+        Tok::Int(digits.parse().map_err(|_| invalid_number(&digits))?)
+    };
+    Ok((token, len + suffix_len))
+}
+
+fn invalid_number(digits: &str) -> Error {
+    Error::new(format!("invalid number `{}`", digits))
+}
+
+/// A cursor over tokens.
+pub struct Parser {
+    tokens: Vec<Tok>,
+    pos: usize,
+}
+
+impl Parser {
+    pub fn new(code: &str) -> Result<Self, Error> {
+        Ok(Self {
+            tokens: tokenize(code)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the punctuation `punct` if it is next.
+    pub fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Tok::Punct(p)) if *p == punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the keyword `keyword` if it is next.
+    pub fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Tok::Ident(ident)) if ident == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", punct)))
+        }
+    }
+
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", keyword)))
+        }
+    }
+
+    pub fn expect_end(&self) -> Result<(), Error> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("end of expression")),
+        }
+    }
+
+    /// Skips all tokens up to the punctuation `punct`, which is not consumed.
+    pub fn skip_until(&mut self, punct: &str) {
+        while matches!(self.peek(), Some(t) if !matches!(t, Tok::Punct(p) if *p == punct)) {
+            self.pos += 1;
+        }
+    }
+
+    pub fn is_end(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        let found = match self.peek() {
+            Some(Tok::Ident(ident)) => format!("`{}`", ident),
+            Some(Tok::Int(value)) => format!("`{}`", value),
+            Some(Tok::Float(value)) => format!("`{}`", value),
+            Some(Tok::Str(value)) => format!("{:?}", value),
+            Some(Tok::Punct(punct)) => format!("`{}`", punct),
+            None => "end of expression".to_string(),
+        };
+        Error::new(format!("unexpected {}, expected {}", found, expected))
+    }
+
+    /// Parses a pattern, type annotations are not part of the pattern.
+    pub fn pattern(&mut self) -> Result<Pattern, Error> {
+        if self.eat("&") {
+            return self.pattern();
+        }
+        if self.eat("(") {
+            let mut patterns = Vec::new();
+            while !self.eat(")") {
+                patterns.push(self.pattern()?);
+                if !self.eat(",") {
+                    self.expect(")")?;
+                    break;
+                }
+            }
+            return Ok(Pattern::Tuple(patterns));
+        }
+        self.eat_keyword("mut");
+        match self.next() {
+            Some(Tok::Ident(ident)) if ident == "_" => Ok(Pattern::Wildcard),
+            Some(Tok::Ident(ident)) => Ok(Pattern::Ident(ident)),
+            _ => {
+                self.pos -= 1;
+                Err(self.unexpected("a pattern"))
+            }
+        }
+    }
+
+    pub fn expr(&mut self) -> Result<Expr, Error> {
+        let mut lhs = self.and()?;
+        while self.eat("||") {
+            lhs = Expr::Binary(Box::new(lhs), BinOp::Or, Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut lhs = self.cmp()?;
+        while self.eat("&&") {
+            lhs = Expr::Binary(Box::new(lhs), BinOp::And, Box::new(self.cmp()?));
+        }
+        Ok(lhs)
+    }
+
+    fn cmp(&mut self) -> Result<Expr, Error> {
+        let lhs = self.unary()?;
+        let ops = [
+            ("==", BinOp::Eq),
+            ("!=", BinOp::Ne),
+            ("<=", BinOp::Le),
+            (">=", BinOp::Ge),
+            ("<", BinOp::Lt),
+            (">", BinOp::Gt),
+        ];
+        for (punct, op) in ops {
+            if self.eat(punct) {
+                return Ok(Expr::Binary(Box::new(lhs), op, Box::new(self.unary()?)));
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.eat("!") {
+            Ok(Expr::Not(Box::new(self.unary()?)))
+        } else if self.eat("-") {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat("&") || self.eat("*") {
+            // References are meaningless for values, accept them for static templates
+            self.unary()
+        } else {
+            self.postfix()
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                let field = match self.next() {
+                    Some(Tok::Ident(ident)) => ident,
+                    Some(Tok::Int(index)) => index.to_string(),
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.unexpected("a field"));
+                    }
+                };
+                if matches!(self.peek(), Some(Tok::Punct("("))) {
+                    return Err(Error::new(format!(
+                        "method call `{}` is not supported, use a filter instead",
+                        field
+                    )));
+                }
+                expr = Expr::Field(Box::new(expr), field);
+            } else if self.eat("[") {
+                let index = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Some(Tok::Int(value)) => Ok(Expr::Literal(Value::Int(value))),
+            Some(Tok::Float(value)) => Ok(Expr::Literal(Value::Float(value))),
+            Some(Tok::Str(value)) => Ok(Expr::Literal(Value::String(value))),
+            Some(Tok::Ident(ident)) => Ok(match ident.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                "None" => Expr::Literal(Value::Null),
+                _ if matches!(self.peek(), Some(Tok::Punct("(" | "::"))) => {
+                    return Err(Error::new(format!(
+                        "function call `{}` is not supported, use a filter instead",
+                        ident
+                    )))
+                }
+                _ => Expr::Var(ident),
+            }),
+            Some(Tok::Punct("(")) => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Tok::Punct("[")) => {
+                let mut items = Vec::new();
+                while !self.eat("]") {
+                    items.push(self.expr()?);
+                    if !self.eat(",") {
+                        self.expect("]")?;
+                        break;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.unexpected("an expression"))
+            }
+        }
+    }
+}
+
+impl Expr {
+    pub fn eval<'e>(&self, env: &'e dyn Env) -> Result<Cow<'e, Value>, Error> {
+        Ok(match self {
+            Expr::Literal(value) => Cow::Owned(value.clone()),
+            Expr::List(items) => {
+                let list = Value::List(
+                    items
+                        .iter()
+                        .map(|item| item.eval(env).map(Cow::into_owned))
+                        .collect::<Result<_, _>>()?,
+                );
+                if list.size() > MAX_SIZE {
+                    return Err(Error::new(format!(
+                        "list is larger than {} bytes",
+                        MAX_SIZE
+                    )));
+                }
+                Cow::Owned(list)
+            }
+            Expr::Var(name) if name == "self" => Cow::Borrowed(env.root()),
+            Expr::Var(name) => Cow::Borrowed(
+                env.lookup(name)
+                    .ok_or_else(|| Error::new(format!("unknown variable `{}`", name)))?,
+            ),
+            Expr::Field(expr, field) => {
+                // Tuple fields like `.0` index lists
+                let field = match field.parse() {
+                    Ok(idx) => Value::Int(idx),
+                    Err(_) => Value::String(field.clone()),
+                };
+                project(expr.eval(env)?, |value| index(value, &field))?
+            }
+            Expr::Index(expr, idx) => {
+                let idx = idx.eval(env)?;
+                project(expr.eval(env)?, |value| index(value, &idx))?
+            }
+            Expr::Not(expr) => Cow::Owned(Value::Bool(!expect_bool(&*expr.eval(env)?)?)),
+            Expr::Neg(expr) => Cow::Owned(match &*expr.eval(env)? {
+                Value::Int(value) => Value::Int(value.checked_neg().ok_or_else(|| {
+                    Error::new(format!("can not negate `{}` without overflow", value))
+                })?),
+                Value::Float(value) => Value::Float(-value),
+                value => {
+                    return Err(Error::new(format!(
+                        "can not negate a {}",
+                        value.type_name()
+                    )))
+                }
+            }),
+            Expr::Binary(lhs, BinOp::And, rhs) => Cow::Owned(Value::Bool(
+                expect_bool(&*lhs.eval(env)?)? && expect_bool(&*rhs.eval(env)?)?,
+            )),
+            Expr::Binary(lhs, BinOp::Or, rhs) => Cow::Owned(Value::Bool(
+                expect_bool(&*lhs.eval(env)?)? || expect_bool(&*rhs.eval(env)?)?,
+            )),
+            Expr::Binary(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.eval(env)?, rhs.eval(env)?);
+                Cow::Owned(Value::Bool(match op {
+                    BinOp::Eq => equals(&lhs, &rhs),
+                    BinOp::Ne => !equals(&lhs, &rhs),
+                    BinOp::Lt => compare(&lhs, &rhs)? == Ordering::Less,
+                    BinOp::Le => compare(&lhs, &rhs)? != Ordering::Greater,
+                    BinOp::Gt => compare(&lhs, &rhs)? == Ordering::Greater,
+                    BinOp::Ge => compare(&lhs, &rhs)? != Ordering::Less,
+                    BinOp::And | BinOp::Or => unreachable!(),
+                }))
+            }
+        })
+    }
+}
+
+/// Applies `f` to a borrowed or owned value, keeping the borrow if possible.
+fn project<'e>(
+    value: Cow<'e, Value>,
+    f: impl for<'v> Fn(&'v Value) -> Result<&'v Value, Error>,
+) -> Result<Cow<'e, Value>, Error> {
+    match value {
+        Cow::Borrowed(value) => f(value).map(Cow::Borrowed),
+        Cow::Owned(value) => f(&value).map(|value| Cow::Owned(value.clone())),
+    }
+}
+
+fn index<'v>(value: &'v Value, idx: &Value) -> Result<&'v Value, Error> {
+    match (value, idx) {
+        (Value::List(items), Value::Int(i)) => usize::try_from(*i)
+            .ok()
+            .and_then(|i| items.get(i))
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "index {} is out of bounds for a list of length {}",
+                    i,
+                    items.len()
+                ))
+            }),
+        (Value::Map(entries), Value::String(key)) => entries
+            .get(key)
+            .ok_or_else(|| Error::new(format!("no field `{}`", key))),
+        (Value::Map(entries), Value::Int(key)) => entries
+            .get(&key.to_string())
+            .ok_or_else(|| Error::new(format!("no field `{}`", key))),
+        (value, idx) => Err(Error::new(format!(
+            "can not index a {} with a {}",
+            value.type_name(),
+            idx.type_name()
+        ))),
+    }
+}
+
+pub fn expect_bool(value: &Value) -> Result<bool, Error> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        value => Err(Error::new(format!(
+            "expected a bool, found a {}",
+            value.type_name()
+        ))),
+    }
+}
+
+/// Converts the prefix of an indent block, either a number of spaces or a string.
+pub fn expect_prefix(value: &Value) -> Result<String, Error> {
+    match value {
+        Value::Int(_) => Ok(" ".repeat(expect_width(value)?)),
+        Value::String(prefix) => Ok(prefix.clone()),
+        value => Err(Error::new(format!(
+            "expected a width or a string, found a {}",
+            value.type_name()
+        ))),
+    }
+}
+
+/// Checks that `value` is an indentation width of at most [`MAX_WIDTH`], so that untrusted
+/// templates can not make the prefix allocate without bound.
+pub fn expect_width(value: &Value) -> Result<usize, Error> {
+    match value {
+        Value::Int(width) => match usize::try_from(*width) {
+            Ok(width) if width <= MAX_WIDTH => Ok(width),
+            _ => Err(Error::new(format!(
+                "expected a width of at most {}, found `{}`",
+                MAX_WIDTH, width
+            ))),
+        },
+        value => Err(Error::new(format!(
+            "expected a width, found a {}",
+            value.type_name()
+        ))),
+    }
+}
+
+fn equals(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Int(lhs), Value::Float(rhs)) | (Value::Float(rhs), Value::Int(lhs)) => {
+            *lhs as f64 == *rhs
+        }
+        (lhs, rhs) => lhs == rhs,
+    }
+}
+
+fn compare(lhs: &Value, rhs: &Value) -> Result<Ordering, Error> {
+    let ordering = match (lhs, rhs) {
+        (Value::Bool(lhs), Value::Bool(rhs)) => Some(lhs.cmp(rhs)),
+        (Value::Int(lhs), Value::Int(rhs)) => Some(lhs.cmp(rhs)),
+        (Value::Int(lhs), Value::Float(rhs)) => (*lhs as f64).partial_cmp(rhs),
+        (Value::Float(lhs), Value::Int(rhs)) => lhs.partial_cmp(&(*rhs as f64)),
+        (Value::Float(lhs), Value::Float(rhs)) => lhs.partial_cmp(rhs),
+        (Value::String(lhs), Value::String(rhs)) => Some(lhs.cmp(rhs)),
+        _ => None,
+    };
+    ordering.ok_or_else(|| {
+        Error::new(format!(
+            "can not compare a {} with a {}",
+            lhs.type_name(),
+            rhs.type_name()
+        ))
+    })
+}
+
+/// Binds the parts of `value` matched by `pattern`.
+pub fn bind(
+    pattern: &Pattern,
+    value: Value,
+    vars: &mut HashMap<String, Value>,
+) -> Result<(), Error> {
+    match (pattern, value) {
+        (Pattern::Ident(name), value) => {
+            vars.insert(name.clone(), value);
+        }
+        (Pattern::Wildcard, _) => (),
+        (Pattern::Tuple(patterns), Value::List(items)) if patterns.len() == items.len() => {
+            for (pattern, item) in patterns.iter().zip(items) {
+                bind(pattern, item, vars)?;
+            }
+        }
+        (Pattern::Tuple(patterns), value) => {
+            return Err(Error::new(format!(
+                "can not destructure a {} into {} values",
+                value.type_name(),
+                patterns.len()
+            )))
+        }
+    }
+    Ok(())
+}
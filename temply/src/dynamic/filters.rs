@@ -0,0 +1,64 @@
+use super::{expr, Error, Value};
+use crate::filters;
+use std::convert::TryFrom;
+
+/// The builtin filters available in dynamic templates.
+const FILTERS: &[&str] = &[
+    "upper", "lower", "trim", "truncate", "indent", "join", "default", "wordwrap", "safe",
+    "noindent",
+];
+
+pub fn exists(name: &str) -> bool {
+    FILTERS.contains(&name)
+}
+
+/// Applies the builtin filter `name`, mirroring the filters of [`temply::filters`](filters).
+pub fn apply(name: &str, value: Value, args: Vec<Value>) -> Result<Value, Error> {
+    let value = match (name, args.as_slice()) {
+        ("upper", []) => filters::upper(value.to_text()?),
+        ("lower", []) => filters::lower(value.to_text()?),
+        ("trim", []) => filters::trim(value.to_text()?),
+        ("truncate", [len]) => filters::truncate(value.to_text()?, expect_usize(len)?),
+        ("indent", [width]) => filters::indent(value.to_text()?, expr::expect_width(width)?),
+        ("wordwrap", [width]) => filters::wordwrap(value.to_text()?, expect_usize(width)?),
+        ("join", [separator]) => match value {
+            Value::List(items) => filters::join(
+                items
+                    .iter()
+                    .map(Value::to_text)
+                    .collect::<Result<Vec<_>, _>>()?,
+                separator.to_text()?,
+            ),
+            value => {
+                return Err(Error::new(format!(
+                    "filter `join` expects a list, found a {}",
+                    value.type_name()
+                )))
+            }
+        },
+        ("default", [default]) => match value {
+            Value::Null => return Ok(default.clone()),
+            value => return Ok(value),
+        },
+        ("safe", []) | ("noindent", []) => return Ok(value),
+        _ => {
+            return Err(Error::new(format!(
+                "filter `{}` does not take {} argument(s)",
+                name,
+                args.len()
+            )))
+        }
+    };
+    Ok(Value::String(value))
+}
+
+fn expect_usize(value: &Value) -> Result<usize, Error> {
+    match value {
+        Value::Int(value) => usize::try_from(*value)
+            .map_err(|_| Error::new(format!("expected a positive int, found `{}`", value))),
+        value => Err(Error::new(format!(
+            "expected an int, found a {}",
+            value.type_name()
+        ))),
+    }
+}
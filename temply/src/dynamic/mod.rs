@@ -0,0 +1,959 @@
+//! A template engine that renders templates at runtime, enabled by the `dynamic` feature.
+//!
+//! Dynamic templates use the same syntax as derived templates, but are parsed at runtime and
+//! rendered against a [`Value`], which can be created from any [`Serialize`] type. This is useful
+//! for templates that are not known at compile time, like user-supplied configuration.
+//!
+//! Since there is no Rust compiler at runtime, expressions are limited to a safe subset: paths
+//! (`user.name`, `self.name`), indexing (`items[0]`, `map["key"]`), literals, comparisons, the
+//! boolean operators `!`, `&&` and `||` and the [builtin filters](crate::filters). A let block
+//! binds a pattern to an expression, a capture block binds it to the output of its body, a for
+//! block iterates over a list or the `(key, value)` pairs of a map. Macros can be called with a
+//! caller, which they render with `{% caller %}`. The prefix of an indent block is a number of
+//! spaces or a string. The formats `{}` and `{:?}` are supported. Match, while, use, include,
+//! import and extends blocks are not supported.
+//!
+//! Rendering fails if macro calls are nested deeper than 64 levels, if it takes more than
+//! 1,000,000 steps, i.e. rendered nodes and loop iterations, if it writes more than 16 MiB,
+//! counting captured output, or if a list built by the template is larger than that. Indentation
+//! widths are limited to 256. This way untrusted templates can neither recurse, expand nor
+//! allocate without bound.
+//!
+//! # Example
+//!
+//! ```
+//! use serde::Serialize;
+//! use temply::dynamic::Template;
+//!
+//! #[derive(Serialize)]
+//! struct Context<'a> {
+//!     name: &'a str,
+//!     items: Vec<i32>,
+//! }
+//!
+//! # fn main() -> Result<(), temply::dynamic::Error> {
+//! let template = Template::new("Hello {{ name | upper }}! {{ items | join(\", \") }}")?;
+//!
+//! let rendered = template.render_to_string(&Context {
+//!     name: "World",
+//!     items: vec![1, 2, 3],
+//! })?;
+//!
+//! assert_eq!(rendered, "Hello WORLD! 1, 2, 3");
+//! # Ok(())
+//! # }
+//! ```
+
+mod expr;
+mod filters;
+mod ser;
+mod value;
+
+pub use self::{ser::to_value, value::Value};
+
+use self::expr::{Env, Expr, Parser, Pattern};
+use crate::__intern::{escape::Html, indent::Indenter};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
+use temply_parser::parser::ast;
+use temply_parser::{lexer, parser, ws};
+
+/// The maximum depth of nested macro calls, to stop runaway recursion in untrusted templates.
+const MAX_DEPTH: usize = 64;
+
+/// The maximum number of rendered nodes and loop iterations, to stop templates that expand
+/// exponentially within [`MAX_DEPTH`], e.g. macros that call each other several times.
+const MAX_STEPS: usize = 1_000_000;
+
+/// The maximum number of bytes written while rendering, including captured output, and the
+/// maximum size of a list built by a template, to stop templates that allocate without bound.
+const MAX_SIZE: usize = 16 * 1024 * 1024;
+
+/// The maximum width of an indent block or the `indent` filter.
+const MAX_WIDTH: usize = 256;
+
+/// The escaping of expressions of a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    None,
+    Html,
+}
+
+impl Default for Escape {
+    fn default() -> Self {
+        Escape::None
+    }
+}
+
+/// The options of a dynamic template, equivalent to the options of the derive.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub escape: Escape,
+    pub dedent: bool,
+}
+
+/// An error while parsing or rendering a dynamic template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    location: Option<(usize, usize)>,
+}
+
+impl Error {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    /// Sets the location of the error to the byte offset `offset` in `source`, unless it already
+    /// has one.
+    fn at(mut self, source: &str, offset: usize) -> Self {
+        if self.location.is_none() {
+            let before = &source[..offset];
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+            self.location = Some((line, column));
+        }
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The `(line, column)` in the template the error occurred at, if known.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        self.location
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Error::new("failed to write the rendered template")
+    }
+}
+
+/// A template that is parsed and rendered at runtime.
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+    nodes: Vec<Node>,
+    escape: Escape,
+}
+
+impl Template {
+    /// Parses a template with the default options.
+    pub fn new(source: impl Into<String>) -> Result<Self, Error> {
+        Self::with_options(source, &Options::default())
+    }
+
+    pub fn with_options(source: impl Into<String>, options: &Options) -> Result<Self, Error> {
+        let source = source.into();
+        let nodes = compile(&source, options)?;
+        Ok(Self {
+            source,
+            nodes,
+            escape: options.escape,
+        })
+    }
+
+    /// Loads and parses a template file. Like for the derive, templates with an `html` extension
+    /// are html-escaped.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|error| Error::new(format!("{}: {}", path.display(), error)))?;
+        let escape = match path.extension() {
+            Some(extension) if extension == "html" => Escape::Html,
+            _ => Escape::None,
+        };
+        Self::with_options(
+            source,
+            &Options {
+                escape,
+                ..Options::default()
+            },
+        )
+    }
+
+    /// Renders the template against `context`, whose fields are available as variables and
+    /// through `self`.
+    pub fn render<T: Serialize + ?Sized>(
+        &self,
+        context: &T,
+        mut buffer: impl fmt::Write,
+    ) -> Result<(), Error> {
+        let root = to_value(context)?;
+        let written = Cell::new(0);
+        let mut renderer = Renderer {
+            template: self,
+            root: &root,
+            frames: vec![Frame::default()],
+            callers: Vec::new(),
+            depth: 0,
+            steps: 0,
+            written: &written,
+        };
+        let result = renderer.render(&self.nodes, &mut Limited::new(&mut buffer, &written));
+        match result {
+            Err(error) if written.get() > MAX_SIZE => Err(Error {
+                message: format!("rendering writes more than {} bytes", MAX_SIZE),
+                ..error
+            }),
+            result => result.map(|_| ()),
+        }
+    }
+
+    pub fn render_to_string<T: Serialize + ?Sized>(&self, context: &T) -> Result<String, Error> {
+        let mut buffer = String::with_capacity(self.source.len());
+        self.render(context, &mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Expr {
+        expr: Expr,
+        filters: Vec<(String, Vec<Expr>)>,
+        debug: bool,
+        ind: usize,
+        at: usize,
+    },
+    Let {
+        pattern: Pattern,
+        expr: Expr,
+        at: usize,
+    },
+    Scope(Vec<Node>),
+    Indent {
+        prefix: Expr,
+        body: Vec<Node>,
+        at: usize,
+    },
+    Capture {
+        pattern: Pattern,
+        body: Vec<Node>,
+        at: usize,
+    },
+    For {
+        label: Option<String>,
+        pattern: Pattern,
+        iter: Expr,
+        pre: String,
+        body: Vec<Node>,
+        else_: Option<Vec<Node>>,
+        at: usize,
+    },
+    Break(Option<String>),
+    Continue(Option<String>),
+    If {
+        branches: Vec<(Expr, Vec<Node>, usize)>,
+        else_: Option<Vec<Node>>,
+    },
+    Macro {
+        name: String,
+        macro_: Macro,
+    },
+    Call {
+        name: String,
+        args: Vec<Expr>,
+        caller: Vec<Node>,
+        ind: usize,
+        at: usize,
+    },
+    Caller {
+        ind: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Macro {
+    params: Vec<Pattern>,
+    body: Vec<Node>,
+}
+
+fn compile(source: &str, options: &Options) -> Result<Vec<Node>, Error> {
+    let tokens = lexer::lex(source, &lexer::Syntax::default());
+    let mut ast = parser::parse(source, &tokens)
+        .map_err(|error| Error::new(error.message(source)).at(source, error.span().start))?;
+    if options.dedent {
+        ws::dedent(&mut ast);
+    }
+    ws::trim(&mut ast);
+    ws::indent(&mut ast);
+
+    Compiler {
+        source,
+        loops: RefCell::new(Vec::new()),
+        in_macro: Cell::new(false),
+    }
+    .ast(ast)
+}
+
+struct Compiler<'s> {
+    source: &'s str,
+    /// The labels of the loops around the current item
+    loops: RefCell<Vec<Option<&'s str>>>,
+    /// Whether the current item is inside of a macro, where `caller` is available
+    in_macro: Cell<bool>,
+}
+
+impl<'s> Compiler<'s> {
+    /// Returns the byte offset of `part`, which is a part of the source.
+    fn offset(&self, part: &str) -> usize {
+        part.as_ptr() as usize - self.source.as_ptr() as usize
+    }
+
+    /// Runs `f` on `code` and locates its errors at `code`.
+    fn parse<T>(
+        &self,
+        code: &str,
+        f: impl FnOnce(&mut Parser) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        Parser::new(code)
+            .and_then(|mut parser| {
+                let value = f(&mut parser)?;
+                parser.expect_end()?;
+                Ok(value)
+            })
+            .map_err(|error| error.at(self.source, self.offset(code)))
+    }
+
+    fn unsupported(&self, at: &str, what: &str) -> Error {
+        Error::new(format!("{} not supported in dynamic templates", what))
+            .at(self.source, self.offset(at))
+    }
+
+    /// Compiles the body of a loop.
+    fn loop_body(&self, label: Option<&'s str>, body: ast::Ast<'s>) -> Result<Vec<Node>, Error> {
+        self.loops.borrow_mut().push(label);
+        let body = self.ast(body);
+        self.loops.borrow_mut().pop();
+        body
+    }
+
+    /// Compiles the body of a macro or caller. Loops around it can not be left from inside of it.
+    fn closure_body(&self, body: ast::Ast<'s>, in_macro: bool) -> Result<Vec<Node>, Error> {
+        let loops = self.loops.replace(Vec::new());
+        let in_macro = self.in_macro.replace(in_macro);
+        let body = self.ast(body);
+        self.in_macro.set(in_macro);
+        self.loops.replace(loops);
+        body
+    }
+
+    /// Compiles a break or continue and checks that it is inside of a loop with the label.
+    fn jump(&self, jump: &'s str, keyword: &str) -> Result<Option<String>, Error> {
+        let label = jump[keyword.len()..].trim();
+        let label = if label.is_empty() { None } else { Some(label) };
+        let loops = self.loops.borrow();
+        let found = match label {
+            Some(label) => loops.contains(&Some(label)),
+            None => !loops.is_empty(),
+        };
+        if found {
+            Ok(label.map(str::to_string))
+        } else {
+            let message = match label {
+                Some(label) => format!("`{}` to undeclared label `{}`", keyword, label),
+                None => format!("`{}` outside of a loop", keyword),
+            };
+            Err(Error::new(message).at(self.source, self.offset(jump)))
+        }
+    }
+
+    fn ast(&self, ast: ast::Ast<'s>) -> Result<Vec<Node>, Error> {
+        let mut nodes = Vec::new();
+        for item in ast.items {
+            if let Some(node) = self.item(item)? {
+                nodes.push(node);
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn item(&self, item: ast::Item<'s>) -> Result<Option<Node>, Error> {
+        Ok(Some(match item {
+            ast::Item::Text(text) => Node::Text(text_to_string(text)),
+            ast::Item::Comment(_) => return Ok(None),
+            ast::Item::Expr {
+                expr,
+                filters,
+                format,
+                ind,
+            } => {
+                let debug = match format {
+                    "{}" => false,
+                    "{:?}" => true,
+                    _ => {
+                        return Err(
+                            self.unsupported(format, "formats other than `{}` and `{:?}` are")
+                        )
+                    }
+                };
+                let ind = if filters.iter().any(|filter| filter.name == "noindent") {
+                    0
+                } else {
+                    ind
+                };
+                let filters = filters
+                    .into_iter()
+                    .map(|filter| {
+                        if !filters::exists(filter.name) {
+                            return Err(Error::new(format!("unknown filter `{}`", filter.name))
+                                .at(self.source, self.offset(filter.name)));
+                        }
+                        let args = filter
+                            .args
+                            .iter()
+                            .map(|arg| self.parse(arg, Parser::expr))
+                            .collect::<Result<_, _>>()?;
+                        Ok((filter.name.to_string(), args))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Node::Expr {
+                    expr: self.parse(expr, Parser::expr)?,
+                    filters,
+                    debug,
+                    ind,
+                    at: self.offset(expr),
+                }
+            }
+            ast::Item::Let(let_) => {
+                let (pattern, expr) = self.parse(let_, |parser| {
+                    parser.expect_keyword("let")?;
+                    let pattern = parser.pattern()?;
+                    parser.skip_until("=");
+                    parser.expect("=")?;
+                    Ok((pattern, parser.expr()?))
+                })?;
+                Node::Let {
+                    pattern,
+                    expr,
+                    at: self.offset(let_),
+                }
+            }
+            ast::Item::Scope(body) => Node::Scope(self.ast(body)?),
+            ast::Item::Indent { indent, body } => Node::Indent {
+                prefix: self.parse(indent, Parser::expr)?,
+                body: self.ast(body)?,
+                at: self.offset(indent),
+            },
+            ast::Item::Capture { name, body } => Node::Capture {
+                pattern: self.parse(name, Parser::pattern)?,
+                body: self.ast(body)?,
+                at: self.offset(name),
+            },
+            ast::Item::For {
+                label,
+                for_,
+                pre,
+                body,
+                else_,
+            } => {
+                let (pattern, iter) = self.parse(for_, |parser| {
+                    parser.expect_keyword("for")?;
+                    let pattern = parser.pattern()?;
+                    parser.expect_keyword("in")?;
+                    Ok((pattern, parser.expr()?))
+                })?;
+                Node::For {
+                    label: label.map(str::to_string),
+                    pattern,
+                    iter,
+                    pre: pre.map(text_to_string).unwrap_or_default(),
+                    body: self.loop_body(label, body)?,
+                    else_: else_.map(|body| self.ast(body)).transpose()?,
+                    at: self.offset(for_),
+                }
+            }
+            ast::Item::While { while_, .. } => {
+                return Err(self.unsupported(while_, "while blocks are"))
+            }
+            ast::Item::Break(break_) => Node::Break(self.jump(break_, "break")?),
+            ast::Item::Continue(continue_) => Node::Continue(self.jump(continue_, "continue")?),
+            ast::Item::If {
+                if_,
+                else_ifs,
+                else_,
+            } => {
+                let mut branches = Vec::new();
+                for (keyword, (cond, body)) in Some(("if", if_))
+                    .into_iter()
+                    .chain(else_ifs.into_iter().map(|b| ("else", b)))
+                {
+                    let expr = self.parse(cond, |parser| {
+                        if keyword == "else" {
+                            parser.expect_keyword("else")?;
+                        }
+                        parser.expect_keyword("if")?;
+                        if parser.eat_keyword("let") {
+                            return Err(Error::new("if let is not supported in dynamic templates"));
+                        }
+                        parser.expr()
+                    })?;
+                    branches.push((expr, self.ast(body)?, self.offset(cond)));
+                }
+                Node::If {
+                    branches,
+                    else_: else_.map(|body| self.ast(body)).transpose()?,
+                }
+            }
+            ast::Item::Macro { name, params, body } => Node::Macro {
+                name: name.to_string(),
+                macro_: Macro {
+                    params: params
+                        .iter()
+                        .map(|param| {
+                            self.parse(param, |parser| {
+                                let pattern = parser.pattern()?;
+                                // Skip the type
+                                if parser.eat(":") {
+                                    while !parser.is_end() {
+                                        parser.skip_until(",");
+                                    }
+                                }
+                                Ok(pattern)
+                            })
+                        })
+                        .collect::<Result<_, _>>()?,
+                    body: self.closure_body(body, true)?,
+                },
+            },
+            ast::Item::Call {
+                name,
+                args,
+                caller,
+                ind,
+            } => Node::Call {
+                name: name.to_string(),
+                args: args
+                    .iter()
+                    .map(|arg| self.parse(arg, Parser::expr))
+                    .collect::<Result<_, _>>()?,
+                caller: match caller {
+                    Some(caller) => self.closure_body(caller, self.in_macro.get())?,
+                    None => Vec::new(),
+                },
+                ind,
+                at: self.offset(name),
+            },
+            ast::Item::Caller { caller, ind } => {
+                if !self.in_macro.get() {
+                    return Err(Error::new("`caller` outside of a macro")
+                        .at(self.source, self.offset(caller)));
+                }
+                Node::Caller { ind }
+            }
+            ast::Item::Block { name: _, body } => Node::Scope(self.ast(body)?),
+            ast::Item::Match { match_, .. } => {
+                return Err(self.unsupported(match_, "match blocks are"))
+            }
+            ast::Item::Include(path) => return Err(self.unsupported(path, "include blocks are")),
+            ast::Item::Use(use_) => return Err(self.unsupported(use_, "use blocks are")),
+            ast::Item::Import { path, .. } => {
+                return Err(self.unsupported(path, "import blocks are"))
+            }
+            ast::Item::Extends(path) => return Err(self.unsupported(path, "extends blocks are")),
+            ast::Item::Super(super_) => {
+                return Err(Error::new("super block outside of an extending template")
+                    .at(self.source, self.offset(super_)))
+            }
+        }))
+    }
+}
+
+fn text_to_string(text: ast::Text<'_>) -> String {
+    let mut buffer = String::new();
+    for line in text.lines {
+        buffer.push_str(line.content);
+        buffer.push_str(line.new_line);
+    }
+    buffer.push_str(text.trailing);
+    buffer
+}
+
+/// How rendering continues after a node. Breaks and continues are checked while compiling, so
+/// they always reach a loop with their label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow<'t> {
+    Next,
+    Break(Option<&'t str>),
+    Continue(Option<&'t str>),
+}
+
+#[derive(Default)]
+struct Frame<'t> {
+    vars: HashMap<String, Value>,
+    macros: HashMap<&'t str, &'t Macro>,
+}
+
+struct Renderer<'t> {
+    template: &'t Template,
+    root: &'t Value,
+    frames: Vec<Frame<'t>>,
+    /// The callers of the macros being rendered, with the number of frames at their call
+    callers: Vec<(&'t [Node], usize)>,
+    depth: usize,
+    steps: usize,
+    /// The number of bytes written so far, see [`Limited`]
+    written: &'t Cell<usize>,
+}
+
+impl Env for Renderer<'_> {
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.vars.get(name))
+            .or_else(|| match self.root {
+                Value::Map(fields) => fields.get(name),
+                _ => None,
+            })
+    }
+
+    fn root(&self) -> &Value {
+        self.root
+    }
+}
+
+impl<'t> Renderer<'t> {
+    fn frame(&mut self) -> &mut Frame<'t> {
+        self.frames.last_mut().unwrap()
+    }
+
+    /// Counts a rendering step and fails once [`MAX_STEPS`] is exceeded.
+    fn step(&mut self) -> Result<(), Error> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err(Error::new(format!(
+                "rendering takes more than {} steps",
+                MAX_STEPS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Renders `nodes` in a new frame.
+    fn render_scope(
+        &mut self,
+        nodes: &'t [Node],
+        buffer: &mut dyn Write,
+    ) -> Result<Flow<'t>, Error> {
+        self.frames.push(Frame::default());
+        let result = self.render(nodes, buffer);
+        self.frames.pop();
+        result
+    }
+
+    fn render(&mut self, nodes: &'t [Node], buffer: &mut dyn Write) -> Result<Flow<'t>, Error> {
+        let source = self.template.source.as_str();
+        for node in nodes {
+            self.step()?;
+            match node {
+                Node::Text(text) => buffer.write_str(text)?,
+                Node::Expr {
+                    expr,
+                    filters,
+                    debug,
+                    ind,
+                    at,
+                } => indented(buffer, *ind, |buffer| {
+                    self.render_expr(expr, filters, *debug, buffer)
+                })
+                .map_err(|error| error.at(source, *at))?,
+                Node::Let { pattern, expr, at } => {
+                    let value = expr
+                        .eval(self)
+                        .map_err(|error| error.at(source, *at))?
+                        .into_owned();
+                    expr::bind(pattern, value, &mut self.frame().vars)
+                        .map_err(|error| error.at(source, *at))?;
+                }
+                Node::Scope(body) => match self.render_scope(body, buffer)? {
+                    Flow::Next => (),
+                    flow => return Ok(flow),
+                },
+                Node::Indent { prefix, body, at } => {
+                    let prefix = prefix
+                        .eval(self)
+                        .and_then(|value| expr::expect_prefix(&value))
+                        .map_err(|error| error.at(source, *at))?;
+                    let mut buffer = Indenter::prefixed(buffer, &prefix);
+                    match self.render_scope(body, &mut buffer)? {
+                        Flow::Next => (),
+                        flow => return Ok(flow),
+                    }
+                }
+                Node::Capture { pattern, body, at } => {
+                    let mut capture = String::new();
+                    let mut buffer = Limited::new(&mut capture, self.written);
+                    match self.render_scope(body, &mut buffer)? {
+                        Flow::Next => (),
+                        flow => return Ok(flow),
+                    }
+                    expr::bind(pattern, Value::String(capture), &mut self.frame().vars)
+                        .map_err(|error| error.at(source, *at))?;
+                }
+                Node::For {
+                    label,
+                    pattern,
+                    iter,
+                    pre,
+                    body,
+                    else_,
+                    at,
+                } => {
+                    let items = match iter
+                        .eval(self)
+                        .map_err(|error| error.at(source, *at))?
+                        .into_owned()
+                    {
+                        Value::List(items) => items,
+                        Value::Map(entries) => entries
+                            .into_iter()
+                            .map(|(key, value)| Value::List(vec![Value::String(key), value]))
+                            .collect(),
+                        value => {
+                            return Err(Error::new(format!(
+                                "can not iterate over a {}",
+                                value.type_name()
+                            ))
+                            .at(source, *at))
+                        }
+                    };
+
+                    if items.is_empty() {
+                        if let Some(else_) = else_ {
+                            match self.render_scope(else_, buffer)? {
+                                Flow::Next => (),
+                                flow => return Ok(flow),
+                            }
+                        }
+                        continue;
+                    }
+
+                    let length = items.len();
+                    for (index0, item) in items.into_iter().enumerate() {
+                        self.step()?;
+                        if index0 > 0 {
+                            buffer.write_str(pre)?;
+                        }
+
+                        let mut frame = Frame::default();
+                        frame
+                            .vars
+                            .insert("loop".to_string(), loop_value(index0, length));
+                        expr::bind(pattern, item, &mut frame.vars)
+                            .map_err(|error| error.at(source, *at))?;
+                        self.frames.push(frame);
+                        let result = self.render(body, buffer);
+                        self.frames.pop();
+                        match result? {
+                            Flow::Next => (),
+                            Flow::Break(target)
+                                if target.is_none() || target == label.as_deref() =>
+                            {
+                                break
+                            }
+                            Flow::Continue(target)
+                                if target.is_none() || target == label.as_deref() => {}
+                            flow => return Ok(flow),
+                        }
+                    }
+                }
+                Node::Break(label) => return Ok(Flow::Break(label.as_deref())),
+                Node::Continue(label) => return Ok(Flow::Continue(label.as_deref())),
+                Node::If { branches, else_ } => {
+                    let mut taken = None;
+                    for (cond, body, at) in branches {
+                        let cond = cond
+                            .eval(self)
+                            .and_then(|value| expr::expect_bool(&value))
+                            .map_err(|error| error.at(source, *at))?;
+                        if cond {
+                            taken = Some(body);
+                            break;
+                        }
+                    }
+                    if let Some(body) = taken.or(else_.as_ref()) {
+                        match self.render_scope(body, buffer)? {
+                            Flow::Next => (),
+                            flow => return Ok(flow),
+                        }
+                    }
+                }
+                Node::Macro { name, macro_ } => {
+                    self.frame().macros.insert(name, macro_);
+                }
+                Node::Call {
+                    name,
+                    args,
+                    caller,
+                    ind,
+                    at,
+                } => self
+                    .render_call(name, args, caller, *ind, buffer)
+                    .map_err(|error| error.at(source, *at))?,
+                Node::Caller { ind } => {
+                    // The caller sees the frames of its call, and the callers of the macros
+                    // around the call
+                    let (caller, frames) = self.callers.pop().expect("caller outside of a macro");
+                    let hidden = self.frames.split_off(frames);
+                    let result = indented(buffer, *ind, |buffer| self.render_scope(caller, buffer));
+                    self.frames.extend(hidden);
+                    self.callers.push((caller, frames));
+                    result?;
+                }
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn render_expr(
+        &self,
+        expr: &Expr,
+        filters: &[(String, Vec<Expr>)],
+        debug: bool,
+        buffer: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let mut value = expr.eval(self)?;
+        for (name, args) in filters {
+            let args = args
+                .iter()
+                .map(|arg| arg.eval(self).map(|arg| arg.into_owned()))
+                .collect::<Result<_, _>>()?;
+            value = Cow::Owned(filters::apply(name, value.into_owned(), args)?);
+        }
+
+        let safe = matches!(filters.last(), Some((name, _)) if name == "safe");
+        let mut html;
+        let buffer = if self.template.escape == Escape::Html && !safe {
+            html = Html::new(buffer);
+            &mut html as &mut dyn Write
+        } else {
+            buffer
+        };
+        if debug {
+            value.write_debug(buffer)
+        } else {
+            value.write_display(buffer)
+        }
+    }
+
+    fn render_call(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        caller: &'t [Node],
+        ind: usize,
+        buffer: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let macro_ = self
+            .frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.macros.get(name).copied())
+            .ok_or_else(|| Error::new(format!("unknown macro `{}`", name)))?;
+        if args.len() != macro_.params.len() {
+            return Err(Error::new(format!(
+                "macro `{}` takes {} argument(s), but {} were given",
+                name,
+                macro_.params.len(),
+                args.len()
+            )));
+        }
+        if self.depth == MAX_DEPTH {
+            return Err(Error::new(format!(
+                "macro calls are nested deeper than {}",
+                MAX_DEPTH
+            )));
+        }
+
+        let mut frame = Frame::default();
+        for (param, arg) in macro_.params.iter().zip(args) {
+            expr::bind(param, arg.eval(self)?.into_owned(), &mut frame.vars)?;
+        }
+
+        self.callers.push((caller, self.frames.len()));
+        self.frames.push(frame);
+        self.depth += 1;
+        let result = indented(buffer, ind, |buffer| self.render(&macro_.body, buffer));
+        self.depth -= 1;
+        self.frames.pop();
+        self.callers.pop();
+        result.map(|_| ())
+    }
+}
+
+/// Runs `f` with a buffer, which indents all lines after the first by `ind` spaces.
+fn indented<T>(buffer: &mut dyn Write, ind: usize, f: impl FnOnce(&mut dyn Write) -> T) -> T {
+    if ind == 0 {
+        f(buffer)
+    } else {
+        let indentation = " ".repeat(ind);
+        f(&mut Indenter::new(buffer, &indentation))
+    }
+}
+
+/// A buffer that fails once more than [`MAX_SIZE`] bytes are written through all buffers sharing
+/// `written`.
+struct Limited<'a> {
+    inner: &'a mut dyn Write,
+    written: &'a Cell<usize>,
+}
+
+impl<'a> Limited<'a> {
+    fn new(inner: &'a mut dyn Write, written: &'a Cell<usize>) -> Self {
+        Self { inner, written }
+    }
+}
+
+impl Write for Limited<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.written.set(self.written.get() + s.len());
+        if self.written.get() > MAX_SIZE {
+            return Err(fmt::Error);
+        }
+        self.inner.write_str(s)
+    }
+}
+
+/// Creates the `loop` variable of a for loop.
+fn loop_value(index0: usize, length: usize) -> Value {
+    let fields = [
+        ("index", Value::from(index0 as i64 + 1)),
+        ("index0", Value::from(index0 as i64)),
+        ("first", Value::from(index0 == 0)),
+        ("last", Value::from(index0 + 1 == length)),
+        ("length", Value::from(length as i64)),
+        ("revindex", Value::from((length - index0) as i64)),
+        ("revindex0", Value::from((length - index0 - 1) as i64)),
+    ];
+    Value::Map(
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
@@ -0,0 +1,214 @@
+use super::Error;
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::mem;
+
+/// A dynamic value, which a [`Template`](super::Template) is rendered against.
+///
+/// Values are usually created from any [`Serialize`] type with [`to_value`](super::to_value).
+/// `Option::None` and `()` become [`Null`](Value::Null), structs become maps and tuples become
+/// lists.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// The name of the type of the value, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+
+    /// The approximate number of bytes the value occupies in memory.
+    pub(super) fn size(&self) -> usize {
+        mem::size_of::<Value>()
+            + match self {
+                Value::Null | Value::Bool(_) | Value::Int(_) | Value::Float(_) => 0,
+                Value::String(value) => value.len(),
+                Value::List(items) => items.iter().map(Value::size).sum(),
+                Value::Map(fields) => fields
+                    .iter()
+                    .map(|(key, value)| mem::size_of::<String>() + key.len() + value.size())
+                    .sum(),
+            }
+    }
+
+    /// Writes the value like `Display` does for the corresponding Rust value. Null is written as
+    /// an empty string, lists and maps can not be displayed.
+    pub(super) fn write_display(&self, buffer: &mut dyn Write) -> Result<(), Error> {
+        match self {
+            Value::Null => (),
+            Value::Bool(value) => write!(buffer, "{}", value)?,
+            Value::Int(value) => write!(buffer, "{}", value)?,
+            Value::Float(value) => write!(buffer, "{}", value)?,
+            Value::String(value) => buffer.write_str(value)?,
+            Value::List(_) | Value::Map(_) => {
+                return Err(Error::new(format!(
+                    "a {} can not be displayed, use `@{{:?}}` instead",
+                    self.type_name()
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the value like `Debug` does for the corresponding Rust value.
+    pub(super) fn write_debug(&self, buffer: &mut dyn Write) -> Result<(), Error> {
+        match self {
+            Value::Null => buffer.write_str("None")?,
+            Value::Bool(value) => write!(buffer, "{:?}", value)?,
+            Value::Int(value) => write!(buffer, "{:?}", value)?,
+            Value::Float(value) => write!(buffer, "{:?}", value)?,
+            Value::String(value) => write!(buffer, "{:?}", value)?,
+            Value::List(items) => {
+                buffer.write_char('[')?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        buffer.write_str(", ")?;
+                    }
+                    item.write_debug(buffer)?;
+                }
+                buffer.write_char(']')?;
+            }
+            Value::Map(entries) => {
+                buffer.write_char('{')?;
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        buffer.write_str(", ")?;
+                    }
+                    write!(buffer, "{:?}: ", key)?;
+                    value.write_debug(buffer)?;
+                }
+                buffer.write_char('}')?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the value to a string like `Display` does.
+    pub(super) fn to_text(&self) -> Result<String, Error> {
+        let mut buffer = String::new();
+        self.write_display(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl Default for Value {
+    fn default() -> Self {
+        Value::Null
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+macro_rules! from_int {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::Int(value.into())
+                }
+            }
+        )*
+    };
+}
+
+from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<BTreeMap<String, T>> for Value {
+    fn from(value: BTreeMap<String, T>) -> Self {
+        Value::Map(value.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_none(),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Int(value) => serializer.serialize_i64(*value),
+            Value::Float(value) => serializer.serialize_f64(*value),
+            Value::String(value) => serializer.serialize_str(value),
+            Value::List(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Value::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Writes the value like it is rendered in a template. Lists and maps are written in their
+    /// debug representation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::List(_) | Value::Map(_) => self.write_debug(f).map_err(|_| fmt::Error),
+            _ => self.write_display(f).map_err(|_| fmt::Error),
+        }
+    }
+}
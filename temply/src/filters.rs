@@ -0,0 +1,130 @@
+//! Builtin filters. See the [`syntax module`](../syntax/index.html#filters) on how to use filters
+//! in templates.
+//!
+//! A filter is a function that takes the value as first argument, followed by any number of
+//! additional arguments. Custom filters can be provided with
+//! `#[template(filters = path::to::module)]`.
+
+use crate::{NoIndent, Safe};
+use std::fmt::Display;
+
+/// Converts a value to uppercase.
+pub fn upper(value: impl Display) -> String {
+    value.to_string().to_uppercase()
+}
+
+/// Converts a value to lowercase.
+pub fn lower(value: impl Display) -> String {
+    value.to_string().to_lowercase()
+}
+
+/// Removes leading and trailing whitespace.
+pub fn trim(value: impl Display) -> String {
+    value.to_string().trim().to_string()
+}
+
+/// Truncates a value to at most `len` chars.
+pub fn truncate(value: impl Display, len: usize) -> String {
+    value.to_string().chars().take(len).collect()
+}
+
+/// Indents all lines except the first one by `width` spaces. Empty lines are not indented.
+pub fn indent(value: impl Display, width: usize) -> String {
+    let value = value.to_string();
+    let mut buffer = String::with_capacity(value.len());
+    for (idx, line) in value.split('\n').enumerate() {
+        if idx > 0 {
+            buffer.push('\n');
+            if !line.is_empty() {
+                buffer.extend((0..width).map(|_| ' '));
+            }
+        }
+        buffer.push_str(line);
+    }
+    buffer
+}
+
+/// Joins all items with a separator.
+pub fn join<I>(value: I, separator: impl Display) -> String
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let separator = separator.to_string();
+    let mut buffer = String::new();
+    for (idx, item) in value.into_iter().enumerate() {
+        if idx > 0 {
+            buffer.push_str(&separator);
+        }
+        buffer.push_str(&item.to_string());
+    }
+    buffer
+}
+
+/// Uses a default value if the value is `None`.
+pub fn default(value: impl Optional, default: impl Display) -> String {
+    match value.get() {
+        Some(value) => value.to_string(),
+        None => default.to_string(),
+    }
+}
+
+/// Wraps a value at whitespace, so that no line is longer than `width` chars, unless it consists
+/// of a single word.
+pub fn wordwrap(value: impl Display, width: usize) -> String {
+    let value = value.to_string();
+    let mut buffer = String::with_capacity(value.len());
+    for (idx, line) in value.split('\n').enumerate() {
+        if idx > 0 {
+            buffer.push('\n');
+        }
+
+        let mut len = 0;
+        for word in line.split_whitespace() {
+            let word_len = word.chars().count();
+            if len > 0 && len + 1 + word_len > width {
+                buffer.push('\n');
+                len = 0;
+            } else if len > 0 {
+                buffer.push(' ');
+                len += 1;
+            }
+            buffer.push_str(word);
+            len += word_len;
+        }
+    }
+    buffer
+}
+
+/// Marks a value as safe, see [`Safe`].
+pub fn safe<T>(value: T) -> Safe<T> {
+    Safe(value)
+}
+
+/// Marks a value as not indented, see [`NoIndent`].
+pub fn noindent<T>(value: T) -> NoIndent<T> {
+    NoIndent(value)
+}
+
+/// A value that might be missing, used by [`default`].
+pub trait Optional {
+    type Value: Display;
+
+    fn get(&self) -> Option<&Self::Value>;
+}
+
+impl<T: Display> Optional for Option<T> {
+    type Value = T;
+
+    fn get(&self) -> Option<&Self::Value> {
+        self.as_ref()
+    }
+}
+
+impl<T: Optional + ?Sized> Optional for &T {
+    type Value = T::Value;
+
+    fn get(&self) -> Option<&Self::Value> {
+        (**self).get()
+    }
+}
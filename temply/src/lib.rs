@@ -5,9 +5,17 @@
 //!
 //! The syntax is documented in the [`syntax module`](./syntax/index.html).
 //!
-//! **Warning**: temply currently does not handle html-escaping and is therefore not suitable for
-//! html templates. You may be looking for [askama](https://crates.io/crates/askama) or
-//! [ructe](https://crates.io/crates/ructe).
+//! Expressions are written as is by default. Html-escaping can be enabled per template, see the
+//! [`syntax module`](./syntax/index.html#escaping).
+//!
+//! Templates that only bundle a few arguments can be written as a function with
+//! [`#[temply::template]`](./attr.template.html).
+//!
+//! One-off templates can be rendered in place with [`render!`](./macro.render.html) and
+//! [`write!`](./macro.write.html).
+//!
+//! Templates that are only known at runtime can be rendered with the
+//! [`dynamic module`](./dynamic/index.html), which requires the `dynamic` feature.
 //!
 //! # Example
 //!
@@ -25,24 +33,177 @@
 //!     let template = MyTemplate { name: "World" };
 //!
 //!     // Render
-//!     let mut buffer = String::new();
-//!     template.render(&mut buffer).unwrap();
+//!     let rendered = template.render_to_string();
 //!
-//!     assert_eq!(buffer, "Hello World!");
+//!     assert_eq!(rendered, "Hello World!");
 //! }
 //! ```
 
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
+pub mod filters;
 pub mod syntax;
 
 use std::fmt;
+use std::io;
 
 #[cfg(feature = "derive")]
-pub use temply_derive::Template;
+pub use temply_derive::{render, template, write, Template};
 
 /// The template trait. Usually this is implemented by deriving
 /// [`Template`](./derive.Template.html).
 pub trait Template {
+    /// An estimate of the size of the rendered template in bytes. The derive computes it from the
+    /// static text of the template.
+    const SIZE_HINT: usize = 0;
+
     fn render(&self, buffer: impl fmt::Write) -> fmt::Result;
+
+    /// Renders the template to a new string, which is pre-allocated with
+    /// [`SIZE_HINT`](Self::SIZE_HINT) bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Display`](fmt::Display) implementation of the template returns an error.
+    fn render_to_string(&self) -> String {
+        __intern::io::render_to_string(Self::SIZE_HINT, |buffer| self.render(buffer))
+    }
+
+    /// Renders the template into an [`io::Write`]. The writer is flushed afterwards. Since a
+    /// template is written in many small pieces, unbuffered writers like [`File`](std::fs::File)
+    /// should be written with [`write_to_buffered`](Self::write_to_buffered) instead.
+    ///
+    /// ```
+    /// use std::io::{self, Write};
+    /// use temply::Template;
+    ///
+    /// #[derive(Debug, Template)]
+    /// #[template_inline = "Hello {{ name }}!"]
+    /// struct MyTemplate<'a> {
+    ///     name: &'a str
+    /// }
+    ///
+    /// # fn main() -> io::Result<()> {
+    /// let stdout = io::stdout();
+    /// MyTemplate { name: "World" }.write_to(stdout.lock())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn write_to(&self, writer: impl io::Write) -> io::Result<()> {
+        __intern::io::write_to(writer, |writer| self.render(writer))
+    }
+
+    /// Renders the template into an [`io::Write`] through a [`BufWriter`](io::BufWriter), which
+    /// is flushed afterwards.
+    fn write_to_buffered(&self, writer: impl io::Write) -> io::Result<()> {
+        self.write_to(io::BufWriter::new(writer))
+    }
+}
+
+/// Adapts an [`io::Write`] to a [`fmt::Write`]. If writing fails, the underlying [`io::Error`] is
+/// kept and can be retrieved with [`take_error`](Self::take_error).
+#[derive(Debug)]
+pub struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> IoWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Takes the last error of the underlying writer.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Marks a value as safe, so that it is written as is even if escaping is enabled. This also
+/// applies to safe values nested inside the [`Display`](fmt::Display) implementation of another
+/// value.
+///
+/// ```
+/// use temply::{Safe, Template};
+///
+/// #[derive(Debug, Template)]
+/// #[template(escape = "html")]
+/// #[template_inline = "{{ text }} {{ Safe(text) }}"]
+/// struct MyTemplate<'a> {
+///     text: &'a str
+/// }
+///
+/// # fn main() {
+/// let template = MyTemplate { text: "<br>" };
+///
+/// let mut buffer = String::new();
+/// template.render(&mut buffer).unwrap();
+///
+/// assert_eq!(buffer, "&lt;br&gt; <br>");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Safe<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for Safe<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        __intern::escape::raw(|| self.0.fmt(f))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Safe<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        __intern::escape::raw(|| self.0.fmt(f))
+    }
+}
+
+/// Marks a value as not indented, so that its lines are written as is even if the expression it is
+/// written by is indented. The indentation of enclosing calls and indent blocks still applies.
+///
+/// ```
+/// use temply::{NoIndent, Template};
+///
+/// #[derive(Debug, Template)]
+/// #[template_inline = "  {{ text }}\n  {{ NoIndent(text) }}"]
+/// struct MyTemplate<'a> {
+///     text: &'a str
+/// }
+///
+/// # fn main() {
+/// let template = MyTemplate { text: "a\nb" };
+///
+/// let mut buffer = String::new();
+/// template.render(&mut buffer).unwrap();
+///
+/// assert_eq!(buffer, "  a\n  b\n  a\nb");
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct NoIndent<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for NoIndent<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        __intern::indent::raw(|| self.0.fmt(f))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for NoIndent<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        __intern::indent::raw(|| self.0.fmt(f))
+    }
 }
 
 // Compile fail tests
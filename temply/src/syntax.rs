@@ -29,6 +29,27 @@
 //! By default, all whitespaces in the template code are preserved, except at the beginning and end
 //! of blocks, where whitespace control is currently unspecified and subject to change.
 //!
+//! Whitespace around a tag can be removed explicitly by adding a `-` to its delimiter: `{{-`,
+//! `{%-` and `{#-` remove all whitespace (including newlines) before the tag, `-}}`, `-%}` and
+//! `-#}` remove all whitespace after it. The `-` must be separated from the content of the tag by
+//! whitespace, so `{{-1}}` is the expression `-1` and `{#----- section -----#}` is a comment.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! [
+//!     {%- for x in xs -%}
+//!         {{ x }},
+//!     {%- endfor -%}
+//! ]
+//! # "#]
+//! # struct MyTemplate {
+//! #     xs: Vec<i32>
+//! # }
+//! ```
+//!
 //! # Expression
 //!
 //! An expression is any valid Rust expression that implements [`Display`] and is delimited by `{{`
@@ -49,6 +70,100 @@
 //! # }
 //! ```
 //!
+//! # Filters
+//!
+//! The value of an expression can be passed through any number of filters, separated by `|`. A
+//! filter is called like a function with the value as first argument, e.g.
+//! `{{ x | truncate(20) }}` is equivalent to `{{ temply::filters::truncate(x, 20) }}`. Note that the
+//! value is passed as is, so use `&x` to not move `x`. The builtin filters are listed in the
+//! [`filters module`](../filters/index.html), custom filters can be provided with
+//! `#[template(filters = path::to::module)]`.
+//!
+//! Every `|` that is not enclosed in brackets, not part of `||` and followed by a name starts a
+//! filter. Hence, a bitwise or with a name on its right, e.g. `(a | b)`, and closures must be
+//! enclosed in parentheses, while `{{ 6 | 3 }}` is still a bitwise or.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% let x = 4 %}
+//! {{ "World" | upper }}
+//! {{ [1, 2, 3] | join(", ") }}
+//! {{ "  text  " | trim | truncate(2)@{:>4} }}
+//! {{ 1 | 2 }}
+//! {{ (1 | x) }}
+//! # "#]
+//! # struct MyTemplate;
+//! ```
+//!
+//! # Escaping
+//!
+//! By default, the output of expressions is written as is. With `#[template(escape = "html")]` the
+//! output of all expressions is html-escaped, text outside of expressions is never escaped.
+//! Templates loaded from a file with an `html` extension are escaped by default, this can be
+//! disabled with `#[template(escape = "none")]`. Values wrapped in [`Safe`] are never escaped, the
+//! same applies to the output of html-escaped templates, e.g. when written with
+//! `#[template(display)]`.
+//!
+//! ```
+//! # use temply::{Safe, Template};
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template(escape = "html")]
+//! # #[template_inline = r#"
+//! <p>{{ "Tom & Jerry" }}</p>
+//! {{ Safe("<br>") }}
+//! # "#]
+//! # struct MyTemplate;
+//! ```
+//!
+//! # Indentation
+//!
+//! An expression or a call that is only preceded by spaces on its line is indented by them, i.e.
+//! every line break in its output is followed by the same number of spaces. Empty lines are not
+//! indented. Values wrapped in [`NoIndent`] or passed through the `noindent` filter are not
+//! indented by their own expression, but still by the calls around it.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! fn main() {
+//!     {{ "let x = 1;\nprintln!(\"{}\", x);" }}
+//!     {{ "/* a\nb */" | noindent }}
+//! }
+//! # "#]
+//! # struct MyTemplate;
+//! ```
+//!
+//! # Indent
+//!
+//! An indent block writes a prefix at the start of every line of its body, including the first
+//! one. The prefix is a Rust expression, which is either a number of spaces or a string, e.g. tabs,
+//! `"// "` or `"> "`. Empty lines only get the prefix without trailing whitespace. Indent blocks
+//! can be nested and are not affected by [`NoIndent`].
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% indent "/// " %}
+//!     {{ doc }}
+//! {% endindent %}
+//! {% indent 4 %}
+//!     {% indent "> " %}{{ quote }}{% endindent %}
+//! {% endindent %}
+//! # "#]
+//! # struct MyTemplate<'a> {
+//! #     doc: &'a str,
+//! #     quote: &'a str,
+//! # }
+//! ```
+//!
 //! # Let
 //!
 //! A let block is any valid Rust let statement delimited by `{%` and `%}`. Shadowing is allowed.
@@ -66,6 +181,45 @@
 //! # struct MyTemplate;
 //! ```
 //!
+//! # Capture
+//!
+//! A capture block renders its body into a `String`, which is bound to the given name and usable
+//! after the block. Since the body is already escaped in html-escaped templates, write the variable
+//! with the `safe` filter there.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% capture tags %}{% for tag in tags.iter() %} #{{ tag }}{% endfor %}{% endcapture %}
+//! {% if !tags.is_empty() %}
+//!     Tags:{{ tags }} ({{ tags.len() }} chars)
+//! {% endif %}
+//! # "#]
+//! # struct MyTemplate<'a> {
+//! #     tags: Vec<&'a str>,
+//! # }
+//! ```
+//!
+//! # Use
+//!
+//! A use block is any valid Rust use declaration delimited by `{%` and `%}`. Like in Rust, the
+//! imported items are available in the whole enclosing block, or the whole template at the top
+//! level. The semicolon at the end is optional.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% use std::fmt::Write as _; %}
+//! {% use std::collections::BTreeMap %}
+//! {{ BTreeMap::<u32, u32>::new().len() }}
+//! # "#]
+//! # struct MyTemplate;
+//! ```
+//!
 //! # Scope
 //!
 //! A scope block is equivalent to a Rust block expression and can be used to limit the scope of a
@@ -104,6 +258,70 @@
 //! # struct MyTemplate;
 //! ```
 //!
+//! Inside the body, `loop` provides information about the current iteration: `loop.index` and
+//! `loop.index0` count the iterations starting at 1 and 0, `loop.first` and `loop.last` tell if
+//! this is the first or last iteration. If the iterator implements [`ExactSizeIterator`],
+//! `loop.length` is the number of iterations and `loop.revindex` and `loop.revindex0` count the
+//! remaining iterations ending at 1 and 0.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% for name in names %}
+//!     {{ loop.index }}/{{ loop.length }}: {{ name }}{% if !loop.last %},{% endif %}
+//! {% endfor %}
+//! # "#]
+//! # struct MyTemplate {
+//! #     names: Vec<String>
+//! # }
+//! ```
+//!
+//! # While
+//!
+//! A while block is equivalent to a Rust while loop. It starts with `{% while <EXPR> %}` or
+//! `{% while let <PAT> = <EXPR> %}` and ends with `{% endwhile %}`.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% let mut words = text.split_whitespace() %}
+//! {% while let Some(word) = words.next() %}
+//!     [{{ word }}]
+//! {% endwhile %}
+//! # "#]
+//! # struct MyTemplate<'a> {
+//! #     text: &'a str
+//! # }
+//! ```
+//!
+//! # Break and Continue
+//!
+//! `{% break %}` and `{% continue %}` leave a for or while loop early, like in Rust. Loops can be
+//! labeled with `{% 'outer: for <PAT> in <EXPR> %}`, so that `{% break 'outer %}` and
+//! `{% continue 'outer %}` target an outer loop.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% 'rows: for row in rows %}
+//!     {% for cell in row %}
+//!         {% if cell.is_empty() %}{% continue 'rows %}{% endif %}
+//!         {% if *cell == "end" %}{% break 'rows %}{% endif %}
+//!         {{ cell }}
+//!     {% endfor %}
+//! {% endfor %}
+//! # "#]
+//! # struct MyTemplate<'a> {
+//! #     rows: Vec<Vec<&'a str>>
+//! # }
+//! ```
+//!
 //! # If
 //!
 //! The if block is equivalent to a Rust if statement. It starts with `{% if <EXPR> %}` and ends with
@@ -179,6 +397,81 @@
 //! # struct MyTemplate;
 //! ```
 //!
+//! A call can pass a body to the macro by ending it with `{% endcall %}`. Calls with and without a
+//! body can be mixed, an `endcall` closes the outermost call of its block that is not closed yet,
+//! so in `{% call a() %}{% call b() %}{% endcall %}` only `a` has a body. A call without a body
+//! that comes before a call with a body in the same block therefore needs its own block, e.g.
+//! `{% scope %}{% call a() %}{% endscope %}`. The macro renders the body with `{% caller %}`, which
+//! is indented like a call. The body sees the variables at the call, not the ones of the macro.
+//! Without a body, `{% caller %}` renders nothing.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% macro section |title| %}
+//!     <h1>{{ title }}</h1>
+//!     <div>
+//!         {% caller %}
+//!     </div>
+//! {% endmacro %}
+//! {% call section("Items") %}
+//!     {% for item in items %}
+//!         <p>{{ item }}</p>
+//!     {% endfor %}
+//! {% endcall %}
+//! # "#]
+//! # struct MyTemplate<'a> {
+//! #     items: Vec<&'a str>
+//! # }
+//! ```
+//!
+//! # Include
+//!
+//! An include block renders another template file in place. The path is resolved like the path of
+//! `#[template = "<PATH>"]`, relative to the src folder. The included template shares the scope of
+//! the including template, so fields, variables and macros declared before the include can be used
+//! in the included template and vice versa. Includes must not form a cycle.
+//!
+//! ```ignore
+//! {% include "header.template" %}
+//! ```
+//!
+//! # Import
+//!
+//! An import block makes the macros of another template file available, so that they can be shared
+//! between templates. The path is resolved like the path of `#[template = "<PATH>"]`, relative to
+//! the src folder. The imported template may only contain macros, comments and whitespace. With
+//! `{% import "<PATH>" as <NAMESPACE> %}`, the macros are called with `{% call <NAMESPACE>::<NAME>
+//! (<ARGS>) %}` instead.
+//!
+//! ```ignore
+//! {% import "macros.template" as fmt %}
+//! {% call fmt::header(self.title) %}
+//! ```
+//!
+//! # Extends and Block
+//!
+//! A template can extend a parent template with `{% extends "<PATH>" %}`. The path is resolved
+//! like the path of `#[template = "<PATH>"]`, relative to the src folder. The parent template
+//! defines named blocks with `{% block <NAME> %}` and `{% endblock %}`, which are overridden by
+//! blocks with the same name in the child template. Overriding a block that no parent template
+//! defines is an error. Everything outside of blocks in the child template is ignored. Inside an
+//! overriding block, `{% super %}` renders the content of the parent block. Templates can be
+//! extended over any number of levels.
+//!
+//! ```ignore
+//! {# base.template #}
+//! <header>{% block title %}Default title{% endblock %}</header>
+//! {% block body %}{% endblock %}
+//!
+//! {# child.template #}
+//! {% extends "base.template" %}
+//! {% block title %}{{ title }} - {% super %}{% endblock %}
+//! {% block body %}Hello World!{% endblock %}
+//! ```
+//!
 //! # Comment
 //!
 //! A comment is any text delimited by `{#` and `#}`. Comments may be nested. Comments must always
@@ -195,4 +488,43 @@
 //! # struct MyTemplate;
 //! ```
 //!
+//! # Raw
+//!
+//! The content of a raw block is emitted verbatim up to the first `{% endraw %}`, even if it
+//! contains unbalanced delimiters.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template_inline = r#"
+//! {% raw %}Write {{ x }} to output x, and {% to start a block.{% endraw %}
+//! # "#]
+//! # struct MyTemplate;
+//! ```
+//!
+//! # Delimiters
+//!
+//! The delimiters can be changed per template with the `syntax` option, e.g. for templates that
+//! produce output containing `{{` or `{%` themselves. Delimiters that are not given keep their
+//! default. All delimiters must be non-empty and distinct, and the trim marker `-` works the same.
+//!
+//! ```
+//! # use temply::Template;
+//! #
+//! # #[derive(Debug, Template)]
+//! # #[template(syntax(expr = ("<<", ">>"), block = ("<%", "%>")))]
+//! # #[template_inline = r#"
+//! \section{<< title >>}
+//! <% for x in xs %>\item{<< x >>}<% endfor %>
+//! # "#]
+//! # struct MyTemplate<'a> {
+//! #     title: &'a str,
+//! #     xs: Vec<i32>,
+//! # }
+//! ```
+//!
 //! [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+//! [`ExactSizeIterator`]: https://doc.rust-lang.org/std/iter/trait.ExactSizeIterator.html
+//! [`NoIndent`]: ../struct.NoIndent.html
+//! [`Safe`]: ../struct.Safe.html
@@ -55,7 +55,7 @@ fn test_enum() {
     #[derive(Debug, Template)]
     #[template_inline = "Enum ..."]
     enum MyTemplate {
-        A(i32),
+        A(#[allow(unused)] i32),
         #[allow(unused)]
         B,
         #[allow(unused)]
@@ -74,6 +74,7 @@ fn test_method() {
     struct MyTemplate;
 
     impl MyTemplate {
+        #[allow(clippy::overly_complex_bool_expr)]
         fn x(&self, v: bool) -> bool {
             v && !v
         }
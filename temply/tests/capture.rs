@@ -0,0 +1,89 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_capture() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% capture greeting %}Hello {{ name }}!{% endcapture %}{{ greeting.len() }}: {{ greeting }}"]
+    struct MyTemplate<'a> {
+        name: &'a str,
+    }
+
+    assert_render!(MyTemplate { name: "World" }, "12: Hello World!");
+}
+
+#[test]
+fn test_empty() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% capture items %}{% for x in xs.iter() %}{{ x }},{% endfor %}{% endcapture %}{% if items.is_empty() %}none{% else %}[{{ items }}]{% endif %}"]
+    struct MyTemplate {
+        xs: Vec<i32>,
+    }
+
+    assert_render!(MyTemplate { xs: vec![] }, "none");
+    assert_render!(MyTemplate { xs: vec![1, 2] }, "[1,2,]");
+}
+
+#[test]
+fn test_pad() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% capture cell %}{{ a }}/{{ b }}{% endcapture %}|{{ cell@{:>6} }}|"]
+    struct MyTemplate {
+        a: i32,
+        b: i32,
+    }
+
+    assert_render!(MyTemplate { a: 1, b: 20 }, "|  1/20|");
+}
+
+#[test]
+fn test_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% capture outer %}({% capture inner %}x{% endcapture %}{{ inner }}{{ inner }}){% endcapture %}{{ outer }}{{ outer }}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "(xx)(xx)");
+}
+
+#[test]
+fn test_loop() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for x in xs.iter() %}{% capture item %}{% if *x == 2 %}{% continue %}{% endif %}{{ x }}{% endcapture %}<{{ item }}>{% endfor %}"]
+    struct MyTemplate {
+        xs: Vec<i32>,
+    }
+
+    assert_render!(MyTemplate { xs: vec![1, 2, 3] }, "<1><3>");
+}
+
+#[test]
+fn test_macro() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro bold |text: &str| %}**{{ text }}**{% endmacro %}{% capture text %}{% call bold(\"a\") %}{% endcapture %}{{ text.to_uppercase() }}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "**A**");
+}
+
+#[test]
+fn test_escape() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{% capture link %}<a>{{ text }}</a>{% endcapture %}{{ link | safe }}"]
+    struct MyTemplate<'a> {
+        text: &'a str,
+    }
+
+    assert_render!(MyTemplate { text: "&" }, "<a>&amp;</a>");
+}
+
+#[test]
+fn test_indent() {
+    #[derive(Debug, Template)]
+    #[dedent]
+    #[template_inline = "{% capture body %}\n  a\n  b\n{% endcapture %}{\n  {{ body }}\n}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "{\n  a\n  b\n}");
+}
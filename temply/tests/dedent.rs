@@ -198,6 +198,33 @@ fn test_match_indented() {
     assert_render!(MyTemplate(None), "no value");
 }
 
+#[test]
+fn test_caller() {
+    #[derive(Debug, Template)]
+    #[dedent]
+    #[template_inline = r#"
+        {% macro section |title| %}
+            <h1>{{ title }}</h1>
+            <div>
+                {% caller %}
+            </div>
+        {% endmacro %}
+<body>
+    {% call section("A") %}
+        <p>
+            Hello
+        </p>
+    {% endcall %}
+</body>
+"#]
+    struct MyTemplate;
+
+    assert_render!(
+        MyTemplate,
+        "\n        \n<body>\n    <h1>A</h1>\n    <div>\n        <p>\n            Hello\n        </p>\n    </div>\n</body>\n"
+    );
+}
+
 #[test]
 fn test_macro() {
     #[derive(Debug, Template)]
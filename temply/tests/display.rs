@@ -0,0 +1,102 @@
+mod util;
+
+use temply::Template;
+
+#[derive(Debug, Template)]
+#[template(display)]
+#[template_inline = "Hello {{ name }}!"]
+struct Hello<'a> {
+    name: &'a str,
+}
+
+#[test]
+fn test_display() {
+    let hello = Hello { name: "World" };
+    assert_eq!(hello.to_string(), "Hello World!");
+    assert_eq!(format!("[{}]", hello), "[Hello World!]");
+}
+
+#[test]
+fn test_format_spec() {
+    let hello = Hello { name: "World" };
+    assert_eq!(format!("{:>14}", hello), "  Hello World!");
+    assert_eq!(format!("{:-^16}", hello), "--Hello World!--");
+    assert_eq!(format!("{:.5}", hello), "Hello");
+}
+
+#[test]
+fn test_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = "<{{ Hello { name: \"a\" } }}> <{{ Hello { name: \"b\" }@{:<14} }}>"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "<Hello a!> <Hello b!      >");
+}
+
+#[test]
+fn test_escape() {
+    #[derive(Debug, Template)]
+    #[template(display, escape = "html")]
+    #[template_inline = "<b>{{ name }}</b>"]
+    struct Inner<'a> {
+        name: &'a str,
+    }
+
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{{ inner }} {{ inner@{:>16} }} {{ name }}"]
+    struct Outer<'a> {
+        inner: Inner<'a>,
+        name: &'a str,
+    }
+
+    assert_render!(
+        Outer {
+            inner: Inner { name: "&" },
+            name: "<>"
+        },
+        "<b>&amp;</b>     <b>&amp;</b> &lt;&gt;"
+    );
+}
+
+#[test]
+fn test_escape_unsafe() {
+    #[derive(Debug, Template)]
+    #[template(display)]
+    #[template_inline = "<b>{{ name }}</b>"]
+    struct Inner<'a> {
+        name: &'a str,
+    }
+
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{{ inner }}"]
+    struct Outer<'a> {
+        inner: Inner<'a>,
+    }
+
+    assert_render!(
+        Outer {
+            inner: Inner { name: "&" },
+        },
+        "&lt;b&gt;&amp;&lt;/b&gt;"
+    );
+}
+
+#[test]
+fn test_named() {
+    #[derive(Debug, Template)]
+    #[template(display)]
+    #[template(name = "short", inline = "{{ name }}")]
+    #[template_inline = "Hello {{ name }}!"]
+    struct MyTemplate<'a> {
+        name: &'a str,
+    }
+
+    let template = MyTemplate { name: "World" };
+    assert_eq!(template.to_string(), "Hello World!");
+
+    let mut buffer = String::new();
+    template.render_short(&mut buffer).unwrap();
+    assert_eq!(buffer, "World");
+}
@@ -0,0 +1,435 @@
+#![cfg(feature = "dynamic")]
+
+mod util;
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use temply::dynamic::{self, Escape, Options, Value};
+
+fn render(source: &str, context: &impl Serialize) -> String {
+    dynamic::Template::new(source)
+        .unwrap()
+        .render_to_string(context)
+        .unwrap()
+}
+
+fn render_error(source: &str, context: &impl Serialize) -> String {
+    match dynamic::Template::new(source) {
+        Ok(template) => template.render_to_string(context).unwrap_err().to_string(),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[derive(Serialize)]
+struct User<'a> {
+    name: &'a str,
+    age: i32,
+    tags: Vec<&'a str>,
+    nick: Option<&'a str>,
+}
+
+const USER: User<'static> = User {
+    name: "World",
+    age: 42,
+    tags: Vec::new(),
+    nick: None,
+};
+
+#[test]
+fn test_expr() {
+    assert_eq!(render("Hello {{ name }}!", &USER), "Hello World!");
+    assert_eq!(render("{{ self.age }} {{ age@{:?} }}", &USER), "42 42");
+    assert_eq!(
+        render("{{ name@{:?} }} {{ tags@{:?} }}", &USER),
+        "\"World\" []"
+    );
+    assert_eq!(
+        render("{{ 1.5 }} {{ -3 }} {{ true }} {{ 'c' }}", &()),
+        "1.5 -3 true c"
+    );
+    assert_eq!(render("[{{ nick }}]", &USER), "[]");
+}
+
+#[test]
+fn test_paths() {
+    let mut inner = BTreeMap::new();
+    inner.insert("list", vec![(1, "a"), (2, "b")]);
+    let mut context = BTreeMap::new();
+    context.insert("outer", inner);
+
+    assert_eq!(
+        render(
+            r#"{{ outer.list[1].1 }} {{ outer["list"][0].0 }} {{ outer.list@{:?} }}"#,
+            &context
+        ),
+        r#"b 1 [[1, "a"], [2, "b"]]"#
+    );
+}
+
+#[test]
+fn test_operators() {
+    assert_eq!(
+        render(
+            "{{ age > 18 }} {{ age == 42.0 }} {{ name != \"World\" }} {{ !(age <= 42) || name >= \"A\" && age < 100 }}",
+            &USER
+        ),
+        "true true false true"
+    );
+}
+
+#[test]
+fn test_filters() {
+    assert_eq!(
+        render(
+            "{{ name | upper }} {{ name | truncate(3) | lower }} {{ [1, 2, 3] | join(\", \") }} {{ nick | default(\"none\") }}",
+            &USER
+        ),
+        "WORLD wor 1, 2, 3 none"
+    );
+}
+
+#[test]
+fn test_blocks() {
+    #[derive(Serialize)]
+    struct Context {
+        xs: Vec<i32>,
+        map: BTreeMap<&'static str, i32>,
+    }
+
+    let mut map = BTreeMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    let context = Context {
+        xs: vec![1, 2, 3],
+        map,
+    };
+
+    assert_eq!(
+        render(
+            "{% for x in xs %}{{ loop.index }}/{{ loop.length }}:{{ x }}{% if !loop.last %},{% endif %}{% endfor %}",
+            &context
+        ),
+        "1/3:1,2/3:2,3/3:3"
+    );
+    assert_eq!(
+        render(
+            "{% for (key, value) in &map %} {{ key }}={{ value }}{% endfor %}",
+            &context
+        ),
+        "a=1 b=2"
+    );
+    assert_eq!(
+        render(
+            "{% for x in [] %}{{ x }}{% else %}empty{% endfor %}",
+            &context
+        ),
+        "empty"
+    );
+    assert_eq!(
+        render(
+            "{% let mut y = xs[2] %}{% scope %}{% let y = 0 %}{{ y }}{% endscope %}{{ y }}",
+            &context
+        ),
+        "03"
+    );
+    assert_eq!(
+        render(
+            "{% if xs[0] > 1 %}a{% else if xs[0] == 1 %}b{% else %}c{% endif %}",
+            &context
+        ),
+        "b"
+    );
+}
+
+#[test]
+fn test_macro() {
+    assert_eq!(
+        render(
+            "{% macro greet |greeting, name: &str| %}{{ greeting }} {{ name }}!{% endmacro %}{% call greet(\"Hello\", name) %}",
+            &USER
+        ),
+        "Hello World!"
+    );
+}
+
+#[test]
+fn test_caller() {
+    assert_eq!(
+        render(
+            "{% macro wrap |name| %}<{{ name }}>{% caller %}</{{ name }}>{% endmacro %}{% call wrap(\"b\") %}{{ name }}{% endcall %}",
+            &USER
+        ),
+        "<b>World</b>"
+    );
+    assert_eq!(
+        render(
+            "{% macro inner || %}({% caller %}){% endmacro %}{% macro outer || %}{% call inner() %}{% caller %}{% endcall %}{% endmacro %}{% call outer() %}x{% endcall %}{% call outer() %}",
+            &USER
+        ),
+        "(x)()"
+    );
+    assert_eq!(
+        render(
+            "{% macro section |n| %}<{{ n }}>[{% caller %}]{% endmacro %}{% macro item |i| %}i{{ i }}{% endmacro %}{% call section(\"s\") %}{% call item(1) %}{% endcall %}",
+            &USER
+        ),
+        "<s>[i1]"
+    );
+    assert_eq!(
+        render_error("{% caller %}", &USER),
+        "1:4: `caller` outside of a macro"
+    );
+}
+
+#[test]
+fn test_indent() {
+    let mut context = BTreeMap::new();
+    context.insert("text", "a\nb");
+
+    assert_eq!(
+        render(
+            "  {{ text }}\n  {{ text | noindent }}\n- {{ text }}",
+            &context
+        ),
+        "  a\n  b\n  a\nb\n- a\nb"
+    );
+    assert_eq!(
+        render(
+            "{% macro lines || %}{{ text }}\n{{ text }}{% endmacro %}  {% call lines() %}",
+            &context
+        ),
+        "  a\n  b\n  a\n  b"
+    );
+    assert_eq!(
+        render("{% indent 2 %}{{ text }}{% endindent %}\n{% indent \"> \" %}{% indent \"> \" %}{{ text }}{% endindent %}{% endindent %}", &context),
+        "  a\n  b\n> > a\n> > b"
+    );
+    assert_eq!(
+        render_error("{% indent true %}{% endindent %}", &context),
+        "1:11: expected a width or a string, found a bool"
+    );
+}
+
+#[test]
+fn test_capture() {
+    assert_eq!(
+        render(
+            "{% capture greeting %}Hello {{ name }}!{% endcapture %}{% if greeting != \"\" %}[{{ greeting | upper }}]{% endif %}",
+            &USER
+        ),
+        "[HELLO WORLD!]"
+    );
+    assert_eq!(
+        render(
+            "{% for x in [1, 2, 3] %}{% capture item %}{% if x == 2 %}{% continue %}{% endif %}{{ x }}{% endcapture %}<{{ item }}>{% endfor %}",
+            &USER
+        ),
+        "<1><3>"
+    );
+}
+
+#[test]
+fn test_escape() {
+    let options = Options {
+        escape: Escape::Html,
+        ..Options::default()
+    };
+    let template =
+        dynamic::Template::with_options("<b>{{ name }}</b> {{ name | safe }}", &options).unwrap();
+
+    assert_eq!(
+        template
+            .render_to_string(&User {
+                name: "<&>",
+                ..USER
+            })
+            .unwrap(),
+        "<b>&lt;&amp;&gt;</b> <&>"
+    );
+}
+
+#[test]
+fn test_value() {
+    let mut context = BTreeMap::new();
+    context.insert("x".to_string(), Value::from(vec![1, 2]));
+    context.insert("y".to_string(), Value::from(Some("a")));
+    let context = Value::from(context);
+
+    assert_eq!(
+        dynamic::to_value(&USER).unwrap(),
+        Value::Map(
+            vec![
+                ("name".to_string(), Value::from("World")),
+                ("age".to_string(), Value::from(42)),
+                ("tags".to_string(), Value::List(Vec::new())),
+                ("nick".to_string(), Value::Null),
+            ]
+            .into_iter()
+            .collect()
+        )
+    );
+    assert_eq!(render("{{ x[1] }}{{ y }}", &context), "2a");
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(render_error("{{ x }}", &USER), "1:4: unknown variable `x`");
+    assert_eq!(
+        render_error("a\n{% if name %}{% endif %}", &USER),
+        "2:4: expected a bool, found a string"
+    );
+    assert_eq!(
+        render_error("{{ name.len() }}", &USER),
+        "1:4: method call `len` is not supported, use a filter instead"
+    );
+    assert_eq!(
+        render_error("{{ name | shout }}", &USER),
+        "1:11: unknown filter `shout`"
+    );
+    assert_eq!(
+        render_error("{{ tags }}", &USER),
+        "1:4: a list can not be displayed, use `@{:?}` instead"
+    );
+    assert_eq!(
+        render_error("{% for x in xs %}", &USER),
+        "1:18: unexpected end of template, expected one of endfor-block"
+    );
+    assert_eq!(
+        render_error("{% include \"a.template\" %}", &USER),
+        "1:13: include blocks are not supported in dynamic templates"
+    );
+    assert_eq!(
+        render_error(
+            "{% macro f || %}{% call f() %}{% endmacro %}{% call f() %}",
+            &()
+        ),
+        "1:25: macro calls are nested deeper than 64"
+    );
+
+    // Every macro calls the previous one twice, which expands to 2^30 calls
+    let mut fan_out = "{% macro m0 || %}x{% endmacro %}".to_string();
+    for i in 1..=30 {
+        fan_out += &format!(
+            "{{% macro m{i} || %}}{{% call m{j}() %}}{{% call m{j}() %}}{{% endmacro %}}",
+            i = i,
+            j = i - 1
+        );
+    }
+    fan_out += "{% call m30() %}";
+    assert!(render_error(&fan_out, &()).ends_with("rendering takes more than 1000000 steps"));
+
+    let mut context = BTreeMap::new();
+    context.insert("text".to_string(), Value::from("a\nb"));
+    context.insert("min".to_string(), Value::from(i64::MIN));
+    let context = Value::from(context);
+    assert_eq!(
+        render_error("{{ -min }}", &context),
+        "1:4: can not negate `-9223372036854775808` without overflow"
+    );
+
+    // Widths, captures and lists must not allocate without bound
+    assert_eq!(
+        render_error("{% indent 99999999999999999 %}x{% endindent %}", &()),
+        "1:11: expected a width of at most 256, found `99999999999999999`"
+    );
+    assert_eq!(
+        render_error("{{ text | indent(99999999999999999) }}", &context),
+        "1:4: expected a width of at most 256, found `99999999999999999`"
+    );
+    assert_eq!(
+        render_error(
+            "{% macro m |s| %}{% capture t %}{{ s }}{{ s }}{% endcapture %}{% call m(t) %}\
+             {% endmacro %}{% call m(\"x\") %}",
+            &()
+        ),
+        "1:36: rendering writes more than 16777216 bytes"
+    );
+    assert_eq!(
+        render_error(
+            "{% macro m |x| %}{% call m([x, x]) %}{% endmacro %}{% call m(1) %}",
+            &()
+        ),
+        "1:26: list is larger than 16777216 bytes"
+    );
+}
+
+#[test]
+fn test_file() {
+    #[derive(Serialize)]
+    struct Context {
+        title: &'static str,
+        items: Vec<&'static str>,
+    }
+
+    let template = dynamic::Template::from_file("tests/templates/escape.html").unwrap();
+    let context = Context {
+        title: "Tom & Jerry",
+        items: vec!["<script>", "\"quoted\""],
+    };
+
+    assert_eq!(
+        template.render_to_string(&context).unwrap(),
+        include_str!("./templates/escape.rendered")
+    );
+}
+
+#[test]
+fn test_static() {
+    #[derive(Debug, Serialize, temply::Template)]
+    #[template = "../tests/templates/dynamic.template"]
+    struct MyTemplate {
+        title: &'static str,
+        items: Vec<(&'static str, i64)>,
+        note: Option<&'static str>,
+    }
+
+    let context = MyTemplate {
+        title: "Shopping list",
+        items: vec![("apple", 3), ("melon", 12)],
+        note: None,
+    };
+
+    let template = dynamic::Template::from_file("tests/templates/dynamic.template").unwrap();
+    assert_eq!(
+        template.render_to_string(&context).unwrap(),
+        temply::Template::render_to_string(&context)
+    );
+}
+
+#[test]
+fn test_break_continue() {
+    #[derive(Serialize)]
+    struct Context {
+        rows: Vec<Vec<i32>>,
+    }
+
+    let context = Context {
+        rows: vec![vec![1, 2, 3], vec![4, 0, 6], vec![7, 8, 9]],
+    };
+
+    assert_eq!(
+        render(
+            "{% 'rows: for row in rows %}{% for x in row %}{% if x == 0 %}{% continue 'rows %}{% endif %}{% if x == 8 %}{% break 'rows %}{% endif %}{% if x == 2 %}{% continue %}{% endif %}{{ x }},{% endfor %};{% endfor %}",
+            &context
+        ),
+        "1,3,;4,7,"
+    );
+    assert_eq!(
+        render_error("{% break %}", &context),
+        "1:4: `break` outside of a loop"
+    );
+    assert_eq!(
+        render_error(
+            "{% for row in rows %}{% continue 'a %}{% endfor %}",
+            &context
+        ),
+        "1:25: `continue` to undeclared label `'a`"
+    );
+    assert_eq!(
+        render_error(
+            "{% for row in rows %}{% macro f || %}{% break %}{% endmacro %}{% endfor %}",
+            &context
+        ),
+        "1:41: `break` outside of a loop"
+    );
+}
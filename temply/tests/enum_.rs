@@ -0,0 +1,88 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_variants() {
+    #[derive(Debug, Template)]
+    enum MyTemplate {
+        #[template_inline = "Named {{ name }} {{ count }}"]
+        Named { name: &'static str, count: usize },
+        #[template_inline = "Positional {{ _0 }} {{ _1 }}"]
+        Positional(&'static str, usize),
+        #[template_inline = "Unit"]
+        Unit,
+    }
+
+    assert_render!(
+        MyTemplate::Named {
+            name: "a",
+            count: 1
+        },
+        "Named a 1"
+    );
+    assert_render!(MyTemplate::Positional("b", 2), "Positional b 2");
+    assert_render!(MyTemplate::Unit, "Unit");
+}
+
+#[test]
+fn test_fallback() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% match self %}{% where Self::B(x) %}B {{ x }}{% endwhere %}{% where _ %}Other{% endwhere %}{% endmatch %}"]
+    enum MyTemplate {
+        #[template_inline = "A"]
+        A,
+        B(i32),
+        C,
+    }
+
+    assert_render!(MyTemplate::A, "A");
+    assert_render!(MyTemplate::B(3), "B 3");
+    assert_render!(MyTemplate::C, "Other");
+}
+
+#[test]
+fn test_file() {
+    #[derive(Debug, Template)]
+    enum MyTemplate {
+        #[template = "../tests/templates/file.template"]
+        File { name: &'static str },
+        #[dedent]
+        #[template(escape = "html")]
+        #[template_inline = "{% scope %}\n    {{ _0 }}\n{% endscope %}"]
+        Inline(&'static str),
+    }
+
+    assert_render!(
+        MyTemplate::File { name: "World" },
+        include_str!("./templates/file.rendered")
+    );
+    assert_render!(MyTemplate::Inline("<>"), "&lt;&gt;");
+}
+
+#[test]
+fn test_whole_enum() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% match self %}{% where Self::A %}A{% endwhere %}{% where Self::B %}B{% endwhere %}{% endmatch %}"]
+    enum MyTemplate {
+        A,
+        B,
+    }
+
+    assert_render!(MyTemplate::A, "A");
+    assert_render!(MyTemplate::B, "B");
+}
+
+#[test]
+fn test_size_hint() {
+    #[allow(dead_code)]
+    #[derive(Debug, Template)]
+    #[template_inline = "ab"]
+    enum MyTemplate {
+        #[template_inline = "abcd"]
+        A,
+        B,
+    }
+
+    assert_eq!(MyTemplate::SIZE_HINT, 4);
+}
@@ -0,0 +1,92 @@
+mod util;
+
+use temply::{Safe, Template};
+
+#[test]
+fn test_none() {
+    #[derive(Debug, Template)]
+    #[template_inline = "<p>{{ self.0 }}</p>"]
+    struct MyTemplate(&'static str);
+
+    assert_render!(MyTemplate("<b>&</b>"), "<p><b>&</b></p>");
+}
+
+#[test]
+fn test_html() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "<p>{{ self.0 }}</p>"]
+    struct MyTemplate(&'static str);
+
+    assert_render!(
+        MyTemplate(r#"<a href="x">'Tom' & 'Jerry'</a>"#),
+        "<p>&lt;a href=&quot;x&quot;&gt;&#x27;Tom&#x27; &amp; &#x27;Jerry&#x27;&lt;/a&gt;</p>"
+    );
+}
+
+#[test]
+fn test_html_format() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{{ self.0@{:?} }} {{ 42@{:>4} }}"]
+    struct MyTemplate(&'static str);
+
+    assert_render!(MyTemplate("<>"), "&quot;&lt;&gt;&quot;   42");
+}
+
+#[test]
+fn test_safe() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{{ Safe(self.0) }} {{ self.1 }}"]
+    struct MyTemplate(&'static str, Safe<&'static str>);
+
+    assert_render!(MyTemplate("<br>", Safe("<hr>")), "<br> <hr>");
+}
+
+#[test]
+fn test_macro() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{% macro x |y| %}<i>{{ y }}</i>{% endmacro %}{% call x(self.0) %}"]
+    struct MyTemplate(&'static str);
+
+    assert_render!(MyTemplate("a < b"), "<i>a &lt; b</i>");
+}
+
+#[test]
+fn test_file() {
+    #[derive(Debug, Template)]
+    #[template = "../tests/templates/escape.html"]
+    struct MyTemplate {
+        title: &'static str,
+        items: Vec<&'static str>,
+    }
+
+    assert_render!(
+        MyTemplate {
+            title: "Tom & Jerry",
+            items: vec!["<script>", "\"quoted\""],
+        },
+        include_str!("./templates/escape.rendered")
+    );
+}
+
+#[test]
+fn test_file_none() {
+    #[derive(Debug, Template)]
+    #[template(escape = "none")]
+    #[template = "../tests/templates/escape.html"]
+    struct MyTemplate {
+        title: &'static str,
+        items: Vec<&'static str>,
+    }
+
+    assert_render!(
+        MyTemplate {
+            title: "Tom & Jerry",
+            items: vec!["<b>"],
+        },
+        "<h1>Tom & Jerry</h1>\n<ul>\n    <li><b></li>\n</ul>\n"
+    );
+}
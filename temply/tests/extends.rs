@@ -0,0 +1,102 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_base() {
+    #[derive(Debug, Template)]
+    #[template = "../tests/templates/base.template"]
+    struct MyTemplate {
+        year: u32,
+    }
+
+    assert_render!(
+        MyTemplate { year: 2022 },
+        "<header>Default title</header>\nBase body\n<footer>(c) 2022</footer>\n"
+    );
+}
+
+#[test]
+fn test_child() {
+    #[derive(Debug, Template)]
+    #[template = "../tests/templates/child.template"]
+    struct MyTemplate {
+        title: &'static str,
+        author: &'static str,
+        year: u32,
+    }
+
+    assert_render!(
+        MyTemplate {
+            title: "Report",
+            author: "Jane",
+            year: 2022,
+        },
+        "<header>Report</header>\nBase body\n<footer>(c) 2022 by Jane</footer>\n"
+    );
+}
+
+#[test]
+fn test_grandchild() {
+    #[derive(Debug, Template)]
+    #[dedent]
+    #[template = "../tests/templates/grandchild.template"]
+    struct MyTemplate {
+        title: &'static str,
+        author: &'static str,
+        year: u32,
+        lines: Vec<&'static str>,
+    }
+
+    assert_render!(
+        MyTemplate {
+            title: "Report",
+            author: "Jane",
+            year: 2022,
+            lines: vec!["a", "b"],
+        },
+        "<header>Report</header>\na\nb\n<footer>[(c) 2022 by Jane]</footer>\n"
+    );
+}
+
+#[test]
+fn test_inline() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% extends "../tests/templates/base.template" %}{% block body %}Inline {{ year }}{% endblock %}"#]
+    struct MyTemplate {
+        year: u32,
+    }
+
+    assert_render!(
+        MyTemplate { year: 2022 },
+        "<header>Default title</header>\nInline 2022\n<footer>(c) 2022</footer>\n"
+    );
+}
+
+#[test]
+fn test_super_twice() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% extends "../tests/templates/base.template" %}{% block title %}{% super %}, {% super %}{% endblock %}"#]
+    struct MyTemplate {
+        year: u32,
+    }
+
+    assert_render!(
+        MyTemplate { year: 2022 },
+        "<header>Default title, Default title</header>\nBase body\n<footer>(c) 2022</footer>\n"
+    );
+}
+
+#[test]
+fn test_nested_blocks() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% extends "../tests/templates/base.template" %}{% block body %}[{% block inner %}inner{% endblock %}]{% endblock %}"#]
+    struct MyTemplate {
+        year: u32,
+    }
+
+    assert_render!(
+        MyTemplate { year: 2022 },
+        "<header>Default title</header>\n[inner]\n<footer>(c) 2022</footer>\n"
+    );
+}
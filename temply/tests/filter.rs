@@ -0,0 +1,129 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_simple() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ name | upper }} {{ name|lower }}"]
+    struct MyTemplate {
+        name: &'static str,
+    }
+
+    assert_render!(MyTemplate { name: "World" }, "WORLD world");
+}
+
+#[test]
+fn test_chain() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ name | trim | upper | truncate(3) }}"]
+    struct MyTemplate {
+        name: &'static str,
+    }
+
+    assert_render!(MyTemplate { name: "  World  " }, "WOR");
+}
+
+#[test]
+fn test_args() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{{ items | join(", ") }}|{{ items | join("|") }}|{{ missing | default('-') }}|{{ Some(1) | default(0) }}"#]
+    struct MyTemplate {
+        items: Vec<i32>,
+        missing: Option<i32>,
+    }
+
+    assert_render!(
+        MyTemplate {
+            items: vec![1, 2, 3],
+            missing: None,
+        },
+        "1, 2, 3|1|2|3|-|1"
+    );
+}
+
+#[test]
+fn test_format() {
+    #[derive(Debug, Template)]
+    #[template_inline = "[{{ name | upper @{:>7} }}]"]
+    struct MyTemplate {
+        name: &'static str,
+    }
+
+    assert_render!(MyTemplate { name: "World" }, "[  WORLD]");
+}
+
+#[test]
+fn test_rust_pipes() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ (a | b) }} {{ *a || *b }} {{ [1, 2].iter().map(|x| x * 2).sum::<i32>() | upper }}"]
+    struct MyTemplate {
+        a: bool,
+        b: bool,
+    }
+
+    assert_render!(MyTemplate { a: false, b: true }, "true true 6");
+}
+
+#[test]
+fn test_bitwise_or() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ 6 | 3 }} {{ 6 | (*a) }} {{ 6|3 | upper }}"]
+    struct MyTemplate {
+        a: i32,
+    }
+
+    assert_render!(MyTemplate { a: 1 }, "7 7 7");
+}
+
+#[test]
+fn test_text() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ text | wordwrap(10) | indent(2) }}"]
+    struct MyTemplate {
+        text: &'static str,
+    }
+
+    assert_render!(
+        MyTemplate {
+            text: "The quick brown fox jumps over the lazy dog"
+        },
+        "The quick\n  brown fox\n  jumps over\n  the lazy\n  dog"
+    );
+}
+
+#[test]
+fn test_safe() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{{ html }} {{ html | safe }}"]
+    struct MyTemplate {
+        html: &'static str,
+    }
+
+    assert_render!(MyTemplate { html: "<br>" }, "&lt;br&gt; <br>");
+}
+
+mod custom {
+    pub use temply::filters::*;
+
+    pub fn upper(value: impl std::fmt::Display) -> String {
+        format!("UPPER({})", value)
+    }
+
+    pub fn quote(value: impl std::fmt::Display, quote: char) -> String {
+        format!("{}{}{}", quote, value, quote)
+    }
+}
+
+#[test]
+fn test_custom() {
+    #[derive(Debug, Template)]
+    #[template(filters = custom)]
+    #[template_inline = "{{ name | upper }} {{ name | lower | quote('\"') }}"]
+    struct MyTemplate {
+        name: &'static str,
+    }
+
+    assert_render!(MyTemplate { name: "World" }, "UPPER(World) \"world\"");
+}
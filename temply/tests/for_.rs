@@ -39,3 +39,40 @@ fn test_else() {
     assert_render!(MyTemplate(1), "0,");
     assert_render!(MyTemplate(0), "Empty");
 }
+
+#[test]
+fn test_loop() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for c in self.0.chars() %}{{ loop.index0 }}{{ loop.index }}{{ loop.first }}{{ loop.last }}{{ c }}{% if !loop.last %},{% endif %}{% endfor %}"]
+    struct MyTemplate(&'static str);
+
+    assert_render!(MyTemplate("ab"), "01truefalsea,12falsetrueb");
+    assert_render!(MyTemplate(""), "");
+}
+
+#[test]
+fn test_loop_sized() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for i in self.0.iter() %}{{ i }}:{{ loop.length }}{{ loop.revindex }}{{ loop.revindex0 }},{% endfor %}"]
+    struct MyTemplate(Vec<i32>);
+
+    assert_render!(MyTemplate(vec![5, 6, 7]), "5:332,6:321,7:310,");
+}
+
+#[test]
+fn test_loop_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for i in 0..2 %}{% let outer = loop %}{% for j in 0..2 %}{{ outer.index }}{{ loop.index }}{% if !loop.last %},{% endif %}{% endfor %};{% endfor %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "11,12;21,22;");
+}
+
+#[test]
+fn test_loop_keyword() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for i in 0..2 %}{{ loop { break i * 2 } }}{% endfor %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "02");
+}
@@ -0,0 +1,93 @@
+mod util;
+
+use std::fmt::Display;
+use temply::Template;
+
+#[test]
+fn test_inline() {
+    #[temply::template(inline = "Hello {{ name }}!")]
+    fn hello(name: &str) -> impl Template {}
+
+    assert_render!(hello("World"), "Hello World!");
+}
+
+#[test]
+fn test_path() {
+    #[temply::template(path = "../tests/templates/file.template")]
+    fn hello(name: &'static str) -> impl Template {}
+
+    assert_render!(hello("World"), "Hello World!\n");
+}
+
+#[test]
+fn test_multiple_args() {
+    #[temply::template(
+        inline = "{{ title }}:{% for item in items.iter() %} {{ item }}{% endfor %}"
+    )]
+    fn list(title: &str, items: &[u32]) -> impl Template {}
+
+    let items = vec![1, 2, 3];
+    assert_render!(list("List", &items), "List:1 2 3");
+}
+
+#[test]
+fn test_lifetimes() {
+    #[derive(Debug)]
+    struct Item<'a> {
+        name: &'a str,
+    }
+
+    #[temply::template(inline = "{{ item.name }}{{ suffix }}")]
+    fn describe<'a>(item: &Item<'_>, suffix: &'a str) -> impl Template {}
+
+    assert_render!(describe(&Item { name: "a" }, "b"), "ab");
+}
+
+#[test]
+fn test_generics() {
+    #[temply::template(inline = "{{ value }}")]
+    fn value<T: Display>(value: T) -> impl Template {}
+
+    assert_render!(value(42), "42");
+}
+
+#[test]
+fn test_struct() {
+    #[temply::template(inline = "{{ name }}")]
+    fn render_name(name: String) -> impl Template {}
+
+    let template: RenderName = render_name("a".to_string());
+    assert_eq!(template.name, "a");
+}
+
+#[test]
+fn test_options() {
+    #[temply::template(inline = "<{{ name }}>", escape = "html", display)]
+    fn tag(name: &str) -> impl Template {}
+
+    assert_eq!(tag("&").to_string(), "<&amp;>");
+}
+
+#[test]
+fn test_dedent() {
+    #[temply::template(
+        inline = "
+            {% for x in xs.iter() %}
+                {{ x }}
+            {% endfor %}",
+        dedent
+    )]
+    fn numbers(xs: &[u32]) -> impl Template {}
+
+    assert_render!(numbers(&[1, 2]), "\n            1\n            2");
+}
+
+#[test]
+fn test_imported() {
+    use temply::template;
+
+    #[template(inline = "{{ x }}")]
+    fn x(x: u32) -> impl Template {}
+
+    assert_render!(x(1), "1");
+}
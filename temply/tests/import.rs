@@ -0,0 +1,57 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_simple() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% import "../tests/templates/macros.template" %}{% call quote("a") %} {% call header("b") %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "\"a\" // \"b\"");
+}
+
+#[test]
+fn test_namespace() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% import "../tests/templates/macros.template" as fmt %}{% call fmt::header("b") %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "// \"b\"");
+}
+
+#[test]
+fn test_namespace_and_local() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% import "../tests/templates/macros.template" as fmt %}{% macro quote |s: &str| %}'{{ s }}'{% endmacro %}{% call quote("a") %} {% call fmt::quote("b") %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "'a' \"b\"");
+}
+
+#[test]
+fn test_dedent() {
+    #[derive(Debug, Template)]
+    #[dedent]
+    #[template_inline = r#"{% import "../tests/templates/macros.template" as fmt %}
+struct A {
+    {% call fmt::doc(&self.0) %}
+    x: u32,
+}
+"#]
+    struct MyTemplate(Vec<&'static str>);
+
+    assert_render!(
+        MyTemplate(vec!["First", "Second"]),
+        "\nstruct A {\n    /// First\n    /// Second\n    x: u32,\n}\n"
+    );
+}
+
+#[test]
+fn test_in_macro() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% macro x || %}{% import "../tests/templates/macros.template" %}{% call quote("a") %}{% endmacro %}{% call x() %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "\"a\"");
+}
@@ -0,0 +1,62 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_simple() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"[{% include "../tests/templates/file.template" %}]"#]
+    struct MyTemplate {
+        name: &'static str,
+    }
+
+    assert_render!(MyTemplate { name: "World" }, "[Hello World!\n]");
+}
+
+#[test]
+fn test_scope() {
+    #[derive(Debug, Template)]
+    #[dedent]
+    #[template = "../tests/templates/include.template"]
+    struct MyTemplate {
+        title: &'static str,
+        items: Vec<&'static str>,
+    }
+
+    assert_render!(
+        MyTemplate {
+            title: "List",
+            items: vec!["a", "b"],
+        },
+        "\n\n# List\n======\n\n- a\n- b\n\n======\n"
+    );
+}
+
+#[test]
+fn test_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% include "../tests/templates/child.template" %}"#]
+    struct MyTemplate {
+        title: &'static str,
+        author: &'static str,
+        year: u32,
+    }
+
+    assert_render!(
+        MyTemplate {
+            title: "Report",
+            author: "Jane",
+            year: 2022,
+        },
+        "<header>Report</header>\nBase body\n<footer>(c) 2022 by Jane</footer>\n"
+    );
+}
+
+#[test]
+fn test_repeated() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% for name in ["a", "b"] %}{% include "../tests/templates/file.template" %}{% endfor %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "Hello a!\nHello b!\n");
+}
@@ -0,0 +1,239 @@
+mod util;
+
+use temply::{NoIndent, Template};
+
+#[test]
+fn test_expr() {
+    #[derive(Debug, Template)]
+    #[template_inline = "fn f() {\n    {{ body }}\n}"]
+    struct MyTemplate<'a> {
+        body: &'a str,
+    }
+
+    assert_render!(
+        MyTemplate {
+            body: "let x = 1;\nx"
+        },
+        "fn f() {\n    let x = 1;\n    x\n}"
+    );
+}
+
+#[test]
+fn test_expr_after_text() {
+    #[derive(Debug, Template)]
+    #[template_inline = "  a: {{ value }}"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(MyTemplate { value: "1\n2" }, "  a: 1\n2");
+}
+
+#[test]
+fn test_empty_lines() {
+    #[derive(Debug, Template)]
+    #[template_inline = "    {{ value }}"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(MyTemplate { value: "a\n\nb\n" }, "    a\n\n    b\n");
+}
+
+#[test]
+fn test_for() {
+    #[derive(Debug, Template)]
+    #[template_inline = "items:\n  {% for item in items.iter() %}\n  {{ item }}{% endfor %}"]
+    struct MyTemplate<'a> {
+        items: &'a [&'a str],
+    }
+
+    assert_render!(
+        MyTemplate {
+            items: &["- a\n  b", "- c"]
+        },
+        "items:\n  - a\n    b\n  - c"
+    );
+}
+
+#[test]
+fn test_call() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro lines || %}a\nb{% endmacro %}{\n    {% call lines() %}\n}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "{\n    a\n    b\n}");
+}
+
+#[test]
+fn test_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro item |value: &str| %}- {{ value }}\n  {{ value }}{% endmacro %}list:\n  {% call item(value) %}"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(
+        MyTemplate { value: "a\nb" },
+        "list:\n  - a\n  b\n    a\n    b"
+    );
+}
+
+#[test]
+fn test_escape() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "<p>\n  {{ value }}\n</p>"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(
+        MyTemplate { value: "<a>\n&" },
+        "<p>\n  &lt;a&gt;\n  &amp;\n</p>"
+    );
+}
+
+#[test]
+fn test_noindent() {
+    #[derive(Debug, Template)]
+    #[template_inline = "  {{ value | noindent }}\n  {{ NoIndent(value) }}\n  {{ value }}"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(MyTemplate { value: "a\nb" }, "  a\nb\n  a\nb\n  a\n  b");
+}
+
+#[test]
+fn test_call_noindent() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro show |value: &str| %}[\n  {{ NoIndent(value) }}\n]{% endmacro %}  {% call show(value) %}"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(MyTemplate { value: "a\nb" }, "  [\n    a\n  b\n  ]");
+}
+
+#[test]
+fn test_unknown_column() {
+    #[derive(Debug, Template)]
+    #[template_inline = "  {% if *prefix %}-{% endif %}{{ value }}"]
+    struct MyTemplate<'a> {
+        prefix: bool,
+        value: &'a str,
+    }
+
+    assert_render!(
+        MyTemplate {
+            prefix: true,
+            value: "a\nb"
+        },
+        "  -a\nb"
+    );
+}
+
+#[test]
+fn test_block_spaces() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% indent 2 %}a\nb{% endindent %}\n{% indent *width %}c{% endindent %}"]
+    struct MyTemplate {
+        width: usize,
+    }
+
+    assert_render!(MyTemplate { width: 4 }, "  a\n  b\n    c");
+}
+
+#[test]
+fn test_block_prefix() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% indent \"/// \" %}{{ doc }}{% endindent %}\nfn f() {}"]
+    struct MyTemplate<'a> {
+        doc: &'a str,
+    }
+
+    assert_render!(
+        MyTemplate {
+            doc: "Does nothing.\n\nReally."
+        },
+        "/// Does nothing.\n///\n/// Really.\nfn f() {}"
+    );
+}
+
+#[test]
+fn test_block_runtime_prefix() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% indent self.quote.repeat(*depth) %}{{ body }}{% endindent %}"]
+    struct MyTemplate<'a> {
+        quote: String,
+        depth: usize,
+        body: &'a str,
+    }
+
+    assert_render!(
+        MyTemplate {
+            quote: "> ".into(),
+            depth: 2,
+            body: "Hi,\nhow are you?"
+        },
+        "> > Hi,\n> > how are you?"
+    );
+}
+
+#[test]
+fn test_block_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% indent '\\t' %}a\n{% indent \"// \" %}b\nc{% endindent %}\nd{% endindent %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "\ta\n\t// b\n\t// c\n\td");
+}
+
+#[test]
+fn test_block_expr() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% indent \"> \" %}{\n  {{ value }}\n}{% endindent %}"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(MyTemplate { value: "a\nb" }, "> {\n>   a\n>   b\n> }");
+}
+
+#[test]
+fn test_block_call() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro list |items: &[&str]| %}{% for item in items.iter() %}\n- {{ item }}{% endfor %}{% endmacro %}{% indent \"> \" %}{% call list(items) %}{% endindent %}"]
+    struct MyTemplate<'a> {
+        items: &'a [&'a str],
+    }
+
+    assert_render!(MyTemplate { items: &["a", "b"] }, "> - a\n> - b");
+}
+
+#[test]
+fn test_block_noindent() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% indent \"# \" %}\n  {{ value | noindent }}\n{% endindent %}"]
+    struct MyTemplate<'a> {
+        value: &'a str,
+    }
+
+    assert_render!(MyTemplate { value: "a\nb" }, "# a\n# b");
+}
+
+#[test]
+fn test_block_dedent() {
+    #[derive(Debug, Template)]
+    #[dedent]
+    #[template_inline = r#"{% scope %}
+    {% indent 2 %}
+        a
+          b
+    {% endindent %}
+{% endscope %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "  a\n    b");
+}
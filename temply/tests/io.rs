@@ -0,0 +1,82 @@
+use std::fmt;
+use std::io::{self, Write};
+use temply::{IoWriter, Template};
+
+#[derive(Debug, Template)]
+#[template_inline = "{% for i in 0..*count %}{{ i }},{% endfor %}"]
+struct MyTemplate {
+    count: usize,
+}
+
+#[test]
+fn test_write_to() {
+    let mut buffer = Vec::new();
+    MyTemplate { count: 4 }.write_to(&mut buffer).unwrap();
+    assert_eq!(buffer, b"0,1,2,3,");
+}
+
+#[test]
+fn test_write_to_buffered() {
+    let mut buffer = Vec::new();
+    MyTemplate { count: 10_000 }
+        .write_to(io::BufWriter::new(&mut buffer))
+        .unwrap();
+    assert_eq!(
+        buffer,
+        MyTemplate { count: 10_000 }.render_to_string().as_bytes()
+    );
+
+    let mut buffer = Vec::new();
+    MyTemplate { count: 10_000 }
+        .write_to_buffered(&mut buffer)
+        .unwrap();
+    assert_eq!(
+        buffer,
+        MyTemplate { count: 10_000 }.render_to_string().as_bytes()
+    );
+}
+
+#[test]
+fn test_write_to_io_error() {
+    struct Failing;
+
+    impl Write for Failing {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let error = MyTemplate { count: 4 }.write_to(Failing).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    assert_eq!(error.to_string(), "broken");
+}
+
+#[test]
+fn test_write_to_fmt_error() {
+    struct Failing;
+
+    impl fmt::Display for Failing {
+        fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ Failing }}"]
+    struct MyTemplate;
+
+    let error = MyTemplate.write_to(Vec::new()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+fn test_io_writer() {
+    let mut writer = IoWriter::new(Vec::new());
+    fmt::Write::write_str(&mut writer, "Hello").unwrap();
+    assert!(writer.take_error().is_none());
+    assert_eq!(writer.into_inner(), b"Hello");
+}
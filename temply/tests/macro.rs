@@ -97,3 +97,93 @@ fn test_arg_mut_ref() {
 
     assert_render!(MyTemplate, "3");
 }
+
+#[test]
+fn test_arg_str() {
+    #[derive(Debug, Template)]
+    #[template_inline = r#"{% macro x |y, z| %}{{ y }}{{ z }}{% endmacro %}{% call x("a, (b", ')') %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "a, (b)");
+}
+
+#[test]
+fn test_caller() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro wrap |tag| %}<{{ tag }}>{% caller %}</{{ tag }}>{% endmacro %}{% call wrap(\"b\") %}{{ self.0 }}{% endcall %}"]
+    struct MyTemplate(&'static str);
+
+    assert_render!(MyTemplate("hi"), "<b>hi</b>");
+}
+
+#[test]
+fn test_caller_repeated() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro twice || %}{% caller %}{% caller %}{% endmacro %}{% call twice() %}ab{% endcall %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "abab");
+}
+
+#[test]
+fn test_caller_without_body() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro x || %}[{% caller %}]{% endmacro %}{% call x() %}{% call x() %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "[][]");
+}
+
+#[test]
+fn test_caller_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro wrap |tag| %}<{{ tag }}>{% caller %}</{{ tag }}>{% endmacro %}{% call wrap(\"a\") %}{% call wrap(\"b\") %}x{% endcall %}{% call wrap(\"c\") %}{% endcall %}{% endcall %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "<a><b>x</b><c></c></a>");
+}
+
+#[test]
+fn test_plain_call_in_caller() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro section |name| %}<{{ name }}>[{% caller %}]{% endmacro %}{% macro item |i| %}i{{ i }}{% endmacro %}{% call section(\"s\") %}{% call item(1) %}{% endcall %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "<s>[i1]");
+}
+
+#[test]
+fn test_plain_calls_around_caller() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro section |name| %}<{{ name }}>[{% caller %}]{% endmacro %}{% macro item |i| %}i{{ i }}{% endmacro %}{% call section(\"s\") %}{% scope %}{% call item(1) %}{% endscope %}{% call section(\"t\") %}{% call item(2) %}{% endcall %}{% call item(3) %}{% endcall %}{% call item(4) %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "<s>[i1<t>[i2]i3]i4");
+}
+
+#[test]
+fn test_caller_in_block() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro x || %}[{% caller %}]{% endmacro %}{% call x() %}{% if true %}{% call x() %}{% endif %}{% endcall %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "[[]]");
+}
+
+#[test]
+fn test_caller_of_caller() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro inner || %}({% caller %}){% endmacro %}{% macro outer || %}{% call inner() %}{% caller %}{% endcall %}{% endmacro %}{% call outer() %}x{% endcall %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "(x)");
+}
+
+#[test]
+fn test_caller_loop() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro item || %}<li>{% caller %}</li>{% endmacro %}{% for x in self.0 %}{% call item() %}{{ x }}{% endcall %}{% endfor %}"]
+    struct MyTemplate(&'static [u32]);
+
+    assert_render!(MyTemplate(&[1, 2]), "<li>1</li><li>2</li>");
+}
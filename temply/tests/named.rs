@@ -0,0 +1,77 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_named() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ name }}"]
+    #[template(name = "short", inline = "{{ name }}: {{ description | truncate(4) }}")]
+    #[template(name = "long", inline = "{{ name }}: {{ description }}")]
+    struct MyTemplate {
+        name: &'static str,
+        description: &'static str,
+    }
+
+    let template = MyTemplate {
+        name: "temply",
+        description: "A template engine",
+    };
+    assert_render!(template, "temply");
+
+    let mut buffer = String::new();
+    template.render_short(&mut buffer).unwrap();
+    assert_eq!(buffer, "temply: A te");
+
+    let mut buffer = String::new();
+    template.render_long(&mut buffer).unwrap();
+    assert_eq!(buffer, "temply: A template engine");
+
+    assert_eq!(template.render_short_to_string(), "temply: A te");
+
+    let mut buffer = Vec::new();
+    template.write_long_to(&mut buffer).unwrap();
+    assert_eq!(buffer, b"temply: A template engine");
+
+    let mut buffer = Vec::new();
+    template.write_short_to_buffered(&mut buffer).unwrap();
+    assert_eq!(buffer, b"temply: A te");
+}
+
+#[test]
+fn test_default() {
+    #[derive(Debug, Template)]
+    #[template(name = "source", path = "../tests/templates/file.template", default)]
+    #[template(name = "header", inline = "// {{ name }}", escape = "html")]
+    struct MyTemplate {
+        name: &'static str,
+    }
+
+    let template = MyTemplate { name: "<World>" };
+    assert_render!(
+        template,
+        include_str!("./templates/file.rendered").replace("World", "<World>")
+    );
+
+    let mut buffer = String::new();
+    template.render_header(&mut buffer).unwrap();
+    assert_eq!(buffer, "// &lt;World&gt;");
+}
+
+#[test]
+fn test_options() {
+    #[derive(Debug, Template)]
+    #[template(escape = "html")]
+    #[template_inline = "{{ 1 }}"]
+    #[template(name = "raw", inline = "{{ \"<>\" }}", escape = "none")]
+    #[template(name = "escaped", inline = "{{ \"<>\" }}!")]
+    struct MyTemplate;
+
+    let mut buffer = String::new();
+    MyTemplate.render_raw(&mut buffer).unwrap();
+    assert_eq!(buffer, "<>");
+
+    let mut buffer = String::new();
+    MyTemplate.render_escaped(&mut buffer).unwrap();
+    assert_eq!(buffer, "&lt;&gt;!");
+}
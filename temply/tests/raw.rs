@@ -0,0 +1,62 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_raw() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% raw %}{{ x }} {% if %} {# y #}{% endraw %} {{ x }}"]
+    struct MyTemplate {
+        x: i32,
+    }
+
+    assert_render!(MyTemplate { x: 1 }, "{{ x }} {% if %} {# y #} 1");
+}
+
+#[test]
+fn test_unbalanced() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% raw %}{% #} }} {{ {#{% endraw %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "{% #} }} {{ {#");
+}
+
+#[test]
+fn test_empty() {
+    #[derive(Debug, Template)]
+    #[template_inline = "a{% raw %}{% endraw %}b"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "ab");
+}
+
+#[test]
+fn test_nested() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for x in xs %}{% raw %}{{ x }}{% endraw %}={{ x }},{% endfor %}"]
+    struct MyTemplate {
+        xs: Vec<i32>,
+    }
+
+    assert_render!(MyTemplate { xs: vec![1, 2] }, "{{ x }}=1,{{ x }}=2,");
+}
+
+#[test]
+fn test_trim() {
+    #[derive(Debug, Template)]
+    #[template_inline = "a\n{%- raw -%}\n  {{ x }}\n{%- endraw -%}\nb"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "a{{ x }}b");
+}
+
+#[test]
+fn test_syntax() {
+    #[derive(Debug, Template)]
+    #[template(syntax(block = ("<%", "%>")))]
+    #[template_inline = "<% raw %>{% raw %}<%raw%><% endraw %>"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "{% raw %}<%raw%>");
+}
@@ -0,0 +1,61 @@
+use temply::Template;
+
+#[test]
+fn test_render_to_string() {
+    #[derive(Debug, Template)]
+    #[template_inline = "Hello {{ name }}!"]
+    struct MyTemplate<'a> {
+        name: &'a str,
+    }
+
+    let rendered = MyTemplate { name: "World" }.render_to_string();
+    assert_eq!(rendered, "Hello World!");
+    assert!(rendered.capacity() >= MyTemplate::SIZE_HINT);
+}
+
+#[test]
+fn test_size_hint_text() {
+    #[derive(Debug, Template)]
+    #[template_inline = "Hello {{ name }}!{# comment #}"]
+    struct MyTemplate<'a> {
+        name: &'a str,
+    }
+
+    assert_eq!(MyTemplate::SIZE_HINT, 7);
+}
+
+#[test]
+fn test_size_hint_blocks() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% if *x %}abcd{% else %}ab{% endif %}{% for _ in 0..3 %}abc{% else %}a{% endfor %}"]
+    struct MyTemplate {
+        x: bool,
+    }
+
+    assert_eq!(MyTemplate::SIZE_HINT, 4 + 3 * 4);
+}
+
+#[test]
+fn test_size_hint_include() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% include \"../tests/templates/include_header.template\" %}"]
+    struct MyTemplate {
+        title: &'static str,
+    }
+
+    assert!(MyTemplate::SIZE_HINT > 0);
+}
+
+#[test]
+fn test_size_hint_default() {
+    struct MyTemplate;
+
+    impl Template for MyTemplate {
+        fn render(&self, mut buffer: impl std::fmt::Write) -> std::fmt::Result {
+            write!(buffer, "Hello")
+        }
+    }
+
+    assert_eq!(MyTemplate::SIZE_HINT, 0);
+    assert_eq!(MyTemplate.render_to_string(), "Hello");
+}
@@ -0,0 +1,96 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+#[test]
+fn test_render() {
+    let v = vec![1, 2, 3];
+    let rendered = temply::render!("{% for x in xs %}{{ x }},{% endfor %}", xs = &v);
+    assert_eq!(rendered, "1,2,3,");
+}
+
+#[test]
+fn test_render_capture() {
+    let name = "World";
+    let items = ["a", "b"];
+    let rendered =
+        temply::render!("Hello {{ name }}! {% for item in items.iter() %}{{ item }};{% endfor %}");
+    assert_eq!(rendered, "Hello World! a;b;");
+    assert_eq!(items.len(), 2);
+}
+
+#[test]
+fn test_render_into() {
+    let mut buffer = String::new();
+    temply::render!(buffer, "{% if x > 1 %}big{% endif %}", x = 2).unwrap();
+    temply::render!(&mut buffer, "{{ x }}", x = 3,).unwrap();
+    assert_eq!(buffer, "big3");
+}
+
+#[test]
+fn test_write() {
+    let mut buffer = String::new();
+    let buffer_ref = &mut buffer;
+    temply::write!(
+        buffer_ref,
+        "{% for x in xs %}{{ x }},{% endfor %}",
+        xs = &[1, 2, 3]
+    )
+    .unwrap();
+    assert_eq!(buffer, "1,2,3,");
+}
+
+#[test]
+fn test_write_io() {
+    let mut buffer = Vec::new();
+    temply::write!(buffer, "{{ a }}-{{ b }}", a = 1, b = "x").unwrap();
+    assert_eq!(buffer, b"1-x");
+}
+
+#[test]
+fn test_macro() {
+    let rendered = temply::render!(
+        "{% macro x |y| %}[{{ y }}]{% endmacro %}{% call x(1) %}{% call x(n) %}",
+        n = 2
+    );
+    assert_eq!(rendered, "[1][2]");
+}
+
+#[test]
+fn test_named_args_order() {
+    let (a, b) = ("A", "B");
+    let rendered = temply::render!("{{ a }}{{ b }}", a = b, b = a);
+    assert_eq!(rendered, "BA");
+}
+
+#[test]
+fn test_format_twice() {
+    struct Twice(String);
+
+    impl std::fmt::Write for Twice {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0.push_str(s);
+            Ok(())
+        }
+
+        fn write_fmt(&mut self, args: std::fmt::Arguments<'_>) -> std::fmt::Result {
+            let first = args.to_string();
+            let second = args.to_string();
+            self.write_str(&first)?;
+            self.write_str(&second)
+        }
+    }
+
+    let v = [String::from("a"), String::from("b")];
+    let mut buffer = Twice(String::new());
+    temply::write!(buffer, "{% for s in v.iter() %}{{ s }}{% endfor %}").unwrap();
+    assert_eq!(buffer.0, "abab");
+}
+
+#[test]
+fn test_include() {
+    let rendered = temply::render!(
+        r#"{% include "../tests/templates/file.template" %}"#,
+        name = "World"
+    );
+    assert_eq!(rendered, "Hello World!\n");
+}
@@ -0,0 +1,69 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_syntax() {
+    #[derive(Debug, Template)]
+    #[template(syntax(expr = ("<<", ">>"), block = ("<%", "%>"), comment = ("<#", "#>")))]
+    #[template_inline = "{{ x }} {% y %} {# z #} <# comment #><< x >><% if *x > 1 %>!<% endif %>"]
+    struct MyTemplate {
+        x: i32,
+    }
+
+    assert_render!(MyTemplate { x: 2 }, "{{ x }} {% y %} {# z #} 2!");
+}
+
+#[test]
+fn test_partial() {
+    #[derive(Debug, Template)]
+    #[template(syntax(expr = ("${", "}")))]
+    #[template_inline = "{% for x in xs %}${ x }{% endfor %} {{ ${ xs.len() }"]
+    struct MyTemplate {
+        xs: Vec<i32>,
+    }
+
+    assert_render!(MyTemplate { xs: vec![1, 2, 3] }, "123 {{ 3");
+}
+
+#[test]
+fn test_trim() {
+    #[derive(Debug, Template)]
+    #[template(syntax(expr = ("[[", "]]"), block = ("[%", "%]")))]
+    #[template_inline = "a\n  [[- x -]]  \nb [%- if true -%]\n c\n[%- endif %]"]
+    struct MyTemplate {
+        x: i32,
+    }
+
+    assert_render!(MyTemplate { x: 1 }, "a1bc");
+}
+
+#[test]
+fn test_latex() {
+    #[derive(Debug, Template)]
+    #[template(syntax(expr = ("(((", ")))"), block = ("((*", "*))")))]
+    #[template_inline = r"\begin{itemize}((* for x in xs.iter() *))\item{((( x )))}((* endfor *))\end{itemize}"]
+    struct MyTemplate<'a> {
+        xs: &'a [&'a str],
+    }
+
+    assert_render!(
+        MyTemplate { xs: &["a", "b"] },
+        r"\begin{itemize}\item{a}\item{b}\end{itemize}"
+    );
+}
+
+#[test]
+fn test_named() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ x }}"]
+    #[template(name = "alt", inline = "{{ x }} <$ x $>", syntax(expr = ("<$", "$>")))]
+    struct MyTemplate {
+        x: i32,
+    }
+
+    let mut buffer = String::new();
+    MyTemplate { x: 1 }.render_alt(&mut buffer).unwrap();
+    assert_eq!(buffer, "{{ x }} 1");
+    assert_render!(MyTemplate { x: 1 }, "1");
+}
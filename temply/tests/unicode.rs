@@ -0,0 +1,89 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_text() {
+    #[derive(Debug, Template)]
+    #[template_inline = "Grüße, {{ name }}! 🦀 {{ 1 + 1 }} ─┼─"]
+    struct MyTemplate<'a> {
+        name: &'a str,
+    }
+
+    assert_render!(MyTemplate { name: "Jürgen" }, "Grüße, Jürgen! 🦀 2 ─┼─");
+}
+
+#[test]
+fn test_expr() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ \"ä\" }}{{ 'ö' }}{{ \"€\".len() }}{{ \"a|ü\" }}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "äö3a|ü");
+}
+
+#[test]
+fn test_blocks() {
+    #[derive(Debug, Template)]
+    #[template_inline = "Übersicht:{% for (name, sum) in rows %}\n│ {{ name }} │ {{ sum }} € │{% endfor %}"]
+    struct MyTemplate {
+        rows: Vec<(&'static str, u32)>,
+    }
+
+    assert_render!(
+        MyTemplate {
+            rows: vec![("Äpfel", 3), ("Öl", 12)],
+        },
+        "Übersicht:│ Äpfel │ 3 € │\n│ Öl │ 12 € │"
+    );
+}
+
+#[test]
+fn test_comment() {
+    #[derive(Debug, Template)]
+    #[template_inline = "ä{# ö #}ü{# 🦀 {# ß #} #}€"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "äü€");
+}
+
+#[test]
+fn test_whitespace() {
+    #[derive(Debug, Template)]
+    #[template_inline = "ä  \n  {{- \"ö\" -}}  \n  ü"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "äöü");
+}
+
+#[test]
+fn test_dedent() {
+    #[derive(Debug, Template)]
+    #[dedent]
+    #[template_inline = r#"{% scope %}
+    Größe:
+        {{ "🦀" }} ─ 1
+    ä
+{% endscope %}"#]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "Größe:\n    🦀 ─ 1\nä");
+}
+
+#[test]
+fn test_filters() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ \"straße\" | upper }} {{ \"ÄÖÜ\" | lower }} {{ \"🦀🦀🦀\" | truncate(2) }}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "STRASSE äöü 🦀🦀");
+}
+
+#[test]
+fn test_macro() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro grüß |name| %}Grüß {{ name }}{% endmacro %}{% call grüß(\"dich\") %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "Grüß dich");
+}
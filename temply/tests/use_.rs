@@ -0,0 +1,64 @@
+mod util;
+
+use temply::Template;
+
+mod helpers {
+    pub fn shout(s: &str) -> String {
+        format!("{}!", s.to_uppercase())
+    }
+
+    pub trait Twice {
+        fn twice(&self) -> u32;
+    }
+
+    impl Twice for u32 {
+        fn twice(&self) -> u32 {
+            self * 2
+        }
+    }
+}
+
+#[test]
+fn test_function() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% use crate::helpers::shout; %}{{ shout(self.0) }}"]
+    struct MyTemplate(&'static str);
+
+    assert_render!(MyTemplate("hi"), "HI!");
+}
+
+#[test]
+fn test_glob() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% use crate::helpers::* %}{{ shout(\"a\") }} {{ self.0.twice() }}"]
+    struct MyTemplate(u32);
+
+    assert_render!(MyTemplate(21), "A! 42");
+}
+
+#[test]
+fn test_after_use() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ shout(\"a\") }}{% use crate::helpers::shout %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "A!");
+}
+
+#[test]
+fn test_scope() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for x in self.0 %}{% use crate::helpers::Twice as _ %}{{ x.twice() }}{% endfor %}"]
+    struct MyTemplate(&'static [u32]);
+
+    assert_render!(MyTemplate(&[1, 2]), "24");
+}
+
+#[test]
+fn test_macro() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% macro x |s: &str| %}{% use crate::helpers::shout %}{{ shout(s) }}{% endmacro %}{% call x(\"b\") %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "B!");
+}
@@ -0,0 +1,76 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_while() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% let mut i = 0 %}{% while i < 4 %}{{ i }},{% let _ = { i += 1 } %}{% endwhile %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "0,1,2,3,");
+}
+
+#[test]
+fn test_while_let() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% let mut it = self.0.iter() %}{% while let Some(x) = it.next() %}[{{ x }}]{% endwhile %}"]
+    struct MyTemplate(Vec<&'static str>);
+
+    assert_render!(MyTemplate(vec!["a", "b"]), "[a][b]");
+    assert_render!(MyTemplate(vec![]), "");
+}
+
+#[test]
+fn test_break() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for i in 0.. %}{% if i == 3 %}{% break %}{% endif %}{{ i }},{% endfor %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "0,1,2,");
+}
+
+#[test]
+fn test_continue() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for i in 0..6 %}{% if i % 2 == 0 %}{% continue %}{% endif %}{{ i }},{% endfor %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "1,3,5,");
+}
+
+#[test]
+fn test_break_while() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% let mut it = self.0.iter() %}{% while let Some(x) = it.next() %}{% if *x == \"end\" %}{% break %}{% endif %}{{ x }}{% endwhile %}"]
+    struct MyTemplate(Vec<&'static str>);
+
+    assert_render!(MyTemplate(vec!["a", "b", "end", "c"]), "ab");
+}
+
+#[test]
+fn test_labels() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% 'outer: for i in 0..4 %}{% for j in 0..4 %}{% if j > i %}{% continue 'outer %}{% endif %}{% if i == 3 %}{% break 'outer %}{% endif %}{{ i }}{{ j }},{% endfor %}{% endfor %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "00,10,11,20,21,22,");
+}
+
+#[test]
+fn test_labels_while() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% let mut i = 0 %}{% 'outer: while i < 10 %}{% let _ = { i += 1 } %}{% for j in 0..i %}{% if i == 3 %}{% break 'outer %}{% endif %}{{ j }}{% endfor %};{% endwhile %}"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "0;01;");
+}
+
+#[test]
+fn test_break_loop() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{% for x in self.0.iter() %}{{ x }}{% if loop.index == 2 %}{% break %}{% endif %}{% endfor %}"]
+    struct MyTemplate(Vec<i32>);
+
+    assert_render!(MyTemplate(vec![1, 2, 3]), "12");
+}
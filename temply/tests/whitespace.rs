@@ -0,0 +1,73 @@
+mod util;
+
+use temply::Template;
+
+#[test]
+fn test_expr() {
+    #[derive(Debug, Template)]
+    #[template_inline = "a  \n  {{- 1 -}}  \n  b  {{- 2 }}  c  {{ 3 -}}  \n  d"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "a1b2  c  3d");
+}
+
+#[test]
+fn test_block() {
+    #[derive(Debug, Template)]
+    #[template_inline = "[\n{%- for i in 0..3 -%}\n    {{ i }},\n{%- endfor -%}\n]"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "[0,1,2,]");
+}
+
+#[test]
+fn test_comment() {
+    #[derive(Debug, Template)]
+    #[template_inline = "a\n  {#- comment -#}\n  b"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "ab");
+}
+
+#[test]
+fn test_negative() {
+    #[derive(Debug, Template)]
+    #[template_inline = "{{ -1 }} {{-1}} {{ 1 - 2 }} {{- -1 }}"]
+    struct MyTemplate;
+
+    // A `-` is only a trim marker if it is followed by whitespace
+    assert_render!(MyTemplate, "-1 -1 -1-1");
+}
+
+#[test]
+fn test_separator_comment() {
+    #[derive(Debug, Template)]
+    #[template_inline = "a\n{#----- section -----#}\nb"]
+    struct MyTemplate;
+
+    assert_render!(MyTemplate, "a\n\nb");
+}
+
+#[test]
+fn test_lines() {
+    #[derive(Debug, Template)]
+    #[template_inline = "items:
+    {%- if *a %} a{% endif %}
+    {%- if *b %} b{% endif %}
+    {%- if *c %} c{% endif %}
+"]
+    struct MyTemplate {
+        a: bool,
+        b: bool,
+        c: bool,
+    }
+
+    assert_render!(
+        MyTemplate {
+            a: true,
+            b: false,
+            c: true,
+        },
+        "items:ac\n"
+    );
+}